@@ -1,19 +1,33 @@
-use crate::{EMPTY_PATTERN, LICENSE_PATTERN, YEARS_PATTERN};
+use crate::inventory::{copyright, strip_decoration};
+use crate::reuse;
+use crate::spdx::validate_expression;
+use crate::{COPYRIGHT_SIGN_PATTERN, EMPTY_PATTERN, LICENSE_PATTERN, SPDX_ID_PATTERN, SPDX_PATTERN, YEARS_PATTERN};
 use encoding_rs;
+use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
 use std::ffi::OsString;
-use std::fs::{read, File};
+use std::fs::{read, read_to_string, File};
 use std::io::{self, prelude::*};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::process::exit;
 use unicode_bom::Bom;
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct Language {
     extensions: Vec<OsString>,
+    file_names: Vec<OsString>,
+    interpreters: Vec<OsString>,
+    priority: u8,
+    content_pattern: Option<Regex>,
+    signature_pattern: Option<Regex>,
     keep_first: Option<Regex>,
+    preambles: Vec<Preamble>,
+    doc_comment_pattern: Option<Regex>,
+    doc_tag_pattern: Option<Regex>,
+    directive_pattern: Option<Regex>,
     block_comment_start_pattern: Option<Regex>,
     block_comment_end_pattern: Option<Regex>,
     line_comment_start_pattern: Option<Regex>,
@@ -22,6 +36,17 @@ struct Language {
     header_end_line: Option<String>,
     header_line_prefix: Option<String>,
     header_line_suffix: Option<String>,
+    // the header has to be followed by an empty line, e.g. to end reStructuredText markup
+    blank_line_after_header: bool,
+}
+
+// lines that stay above the header, from a start line to an end line
+#[derive(Debug, Clone)]
+struct Preamble {
+    start_pattern: Regex,
+    end_pattern: Option<Regex>,
+    // the lines up to the end have to match this, any line does if not given
+    line_pattern: Option<Regex>,
 }
 
 #[allow(dead_code)]
@@ -46,6 +71,54 @@ struct Content {
     lines: Vec<String>,
 }
 
+// language name that can be used in a mapping to skip matching files
+pub const IGNORE_LANGUAGE: &str = "ignore";
+
+// what to do with headers that don't match the owner pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThirdParty {
+    #[default]
+    Skip,
+    Above,
+    Append,
+}
+
+// the header of a file as found by the detection, without line endings
+#[derive(Debug)]
+pub struct Header {
+    pub path: PathBuf,
+    pub language: String,
+    pub lines: Vec<String>,
+}
+
+// a file that doesn't survive updating its header
+#[derive(Debug)]
+pub struct Failure {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub mappings: Vec<(String, String)>,
+    pub owner: Option<Regex>,
+    pub third_party: ThirdParty,
+    pub reuse: bool,
+    pub similarity: Option<f64>,
+}
+
+// what was noticed while updating the files
+#[derive(Debug, Default)]
+pub struct Report {
+    pub cosmetic: Vec<PathBuf>,
+    // kept because of --similarity although the wording differs
+    pub similar: Vec<PathBuf>,
+    // files given a header or a license file, what REUSE mode checks afterwards
+    pub checked: Vec<PathBuf>,
+    // the <file>.license files, to be staged in git mode
+    pub sidecars: Vec<PathBuf>,
+}
+
 macro_rules! osvec {
     ( $( $x:expr ),* ) => {
         {
@@ -58,18 +131,30 @@ macro_rules! osvec {
     };
 }
 
+fn create_preamble(start_pattern: &str, end_pattern: Option<&str>) -> Preamble {
+    Preamble {
+        start_pattern: Regex::new(start_pattern).unwrap(),
+        end_pattern: end_pattern.map(|p| Regex::new(p).unwrap()),
+        line_pattern: None,
+    }
+}
+
 fn create_c_style_language(extensions: Vec<OsString>) -> Language {
     Language {
         extensions,
-        keep_first: None,
+        // /** blocks are only documentation when they carry JSDoc/Javadoc/Doxygen file level tags
+        doc_comment_pattern: Some(Regex::new(r"^\s*/\*\*([^*/]|$)").unwrap()),
+        doc_tag_pattern: Some(
+            Regex::new(r"[@\\](file|fileoverview|overview|module|brief|mainpage|defgroup|page|package|namespace)\b")
+                .unwrap(),
+        ),
         block_comment_start_pattern: Some(Regex::new(r"^\s*/\*").unwrap()),
         block_comment_end_pattern: Some(Regex::new(r"\*/\s*$").unwrap()),
         line_comment_start_pattern: Some(Regex::new(r"^\s*//").unwrap()),
-        line_comment_end_pattern: None,
         header_start_line: Some(String::from("/*")),
         header_end_line: Some(String::from(" */")),
         header_line_prefix: Some(String::from(" * ")),
-        header_line_suffix: None,
+        ..Default::default()
     }
 }
 
@@ -77,145 +162,655 @@ fn create_xml_style_language(extensions: Vec<OsString>) -> Language {
     Language {
         extensions,
         keep_first: Some(Regex::new(r"^\s*<\?xml.*\?>").unwrap()),
+        preambles: vec![create_preamble(r"^\s*<!DOCTYPE", Some(r">\s*$"))],
         block_comment_start_pattern: Some(Regex::new(r"^\s*<!--").unwrap()),
         block_comment_end_pattern: Some(Regex::new(r"-->\s*$").unwrap()),
-        line_comment_start_pattern: None,
-        line_comment_end_pattern: None,
         header_start_line: Some(String::from("<!--")),
         header_end_line: Some(String::from("-->")),
         header_line_prefix: Some(String::from("   ")),
-        header_line_suffix: None,
+        ..Default::default()
+    }
+}
+
+fn create_hash_style_language(extensions: Vec<OsString>) -> Language {
+    Language {
+        extensions,
+        line_comment_start_pattern: Some(Regex::new(r"^\s*#").unwrap()),
+        header_start_line: Some(String::from("##")),
+        header_end_line: Some(String::from("##")),
+        header_line_prefix: Some(String::from("## ")),
+        ..Default::default()
+    }
+}
+
+fn create_double_dash_style_language(extensions: Vec<OsString>) -> Language {
+    Language {
+        extensions,
+        line_comment_start_pattern: Some(Regex::new(r"^\s*--").unwrap()),
+        header_start_line: Some(String::from("--")),
+        header_end_line: Some(String::from("--")),
+        header_line_prefix: Some(String::from("-- ")),
+        ..Default::default()
+    }
+}
+
+fn create_semicolon_style_language(extensions: Vec<OsString>) -> Language {
+    Language {
+        extensions,
+        line_comment_start_pattern: Some(Regex::new(r"^\s*;").unwrap()),
+        header_start_line: Some(String::from(";;")),
+        header_end_line: Some(String::from(";;")),
+        header_line_prefix: Some(String::from(";; ")),
+        ..Default::default()
+    }
+}
+
+fn create_ml_style_language(extensions: Vec<OsString>) -> Language {
+    Language {
+        extensions,
+        block_comment_start_pattern: Some(Regex::new(r"^\s*\(\*").unwrap()),
+        block_comment_end_pattern: Some(Regex::new(r"\*\)\s*$").unwrap()),
+        header_start_line: Some(String::from("(*")),
+        header_end_line: Some(String::from(" *)")),
+        header_line_prefix: Some(String::from(" * ")),
+        ..Default::default()
+    }
+}
+
+fn create_percent_style_language(extensions: Vec<OsString>) -> Language {
+    Language {
+        extensions,
+        line_comment_start_pattern: Some(Regex::new(r"^\s*%").unwrap()),
+        header_start_line: Some(String::from("%")),
+        header_end_line: Some(String::from("%")),
+        header_line_prefix: Some(String::from("% ")),
+        ..Default::default()
     }
 }
 
 #[allow(clippy::vec_init_then_push)]
-fn get_type_settings(languages: &Option<Vec<&str>>) -> HashMap<String, Language> {
-    let type_settings: HashMap<String, Language> = {
-        let mut t = HashMap::new();
-        t.insert("c".to_string(), create_c_style_language(osvec!["c", "cc", "h"]));
-        t.insert(
-            "cpp".to_string(),
-            create_c_style_language(osvec!["cpp", "hpp", "cxx", "hxx", "ixx"]),
-        );
-        t.insert("csharp".to_string(), create_c_style_language(osvec!["cs", "csx"]));
-        t.insert("rust".to_string(), create_c_style_language(osvec!["rs"]));
-        t.insert("go".to_string(), create_c_style_language(osvec!["go"]));
-        t.insert("swift".to_string(), create_c_style_language(osvec!["swift"]));
-        t.insert("objective-c".to_string(), create_c_style_language(osvec!["m", "mm"]));
-        t.insert("kotlin".to_string(), create_c_style_language(osvec!["kt", "kts", "ktm"]));
-        t.insert("java".to_string(), create_c_style_language(osvec!["java", "jape"]));
-        t.insert("javascript".to_string(), create_c_style_language(osvec!["js", "cjs", "mjs"]));
-        t.insert("groovy".to_string(), create_c_style_language(osvec!["groovy"]));
-        t.insert(
-            "php".to_string(),
-            create_c_style_language(osvec![
+fn built_in_languages() -> HashMap<String, Language> {
+    let mut t = HashMap::new();
+    t.insert(
+        "c".to_string(),
+        Language {
+            // .h is shared with C++ and Objective-C, C wins unless --language or --map says otherwise
+            priority: 1,
+            ..create_c_style_language(osvec!["c", "cc", "h"])
+        },
+    );
+    t.insert(
+        "cpp".to_string(),
+        create_c_style_language(osvec!["cpp", "hpp", "cxx", "hxx", "ixx", "h"]),
+    );
+    t.insert("csharp".to_string(), create_c_style_language(osvec!["cs", "csx"]));
+    t.insert(
+        "rust".to_string(),
+        Language {
+            // inner (//! /*!) and outer (/// /**) doc comments, but not //// or /*** decorations
+            doc_comment_pattern: Some(Regex::new(r"^\s*(//!|///([^/]|$)|/\*!|/\*\*([^*/]|$))").unwrap()),
+            doc_tag_pattern: None,
+            ..create_c_style_language(osvec!["rs"])
+        },
+    );
+    t.insert(
+        "go".to_string(),
+        Language {
+            // build constraints are only honoured when followed by an empty line, the header goes below it
+            preambles: vec![Preamble {
+                line_pattern: Some(Regex::new(r"^//").unwrap()),
+                ..create_preamble(r"^//(go:build|\s*\+build)\s", Some(r"^\s*$"))
+            }],
+            ..create_c_style_language(osvec!["go"])
+        },
+    );
+    t.insert(
+        "swift".to_string(),
+        Language {
+            interpreters: osvec!["swift"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            ..create_c_style_language(osvec!["swift"])
+        },
+    );
+    t.insert("objective-c".to_string(), create_c_style_language(osvec!["m", "mm", "h"]));
+    t.insert("kotlin".to_string(), create_c_style_language(osvec!["kt", "kts", "ktm"]));
+    t.insert("java".to_string(), create_c_style_language(osvec!["java", "jape"]));
+    t.insert(
+        "javascript".to_string(),
+        Language {
+            interpreters: osvec!["node", "nodejs"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            ..create_c_style_language(osvec!["js", "cjs", "mjs"])
+        },
+    );
+    t.insert(
+        "groovy".to_string(),
+        Language {
+            file_names: osvec!["Jenkinsfile"],
+            interpreters: osvec!["groovy"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            ..create_c_style_language(osvec!["groovy"])
+        },
+    );
+    t.insert(
+        "php".to_string(),
+        Language {
+            interpreters: osvec!["php"],
+            // the header has to be php code, not output before the opening tag
+            keep_first: Some(Regex::new(r"^#!|^<\?php\s*$").unwrap()),
+            // declare(strict_types=1) has to be the first statement
+            preambles: vec![create_preamble(r"^\s*declare\s*\(", None)],
+            ..create_c_style_language(osvec![
                 "php", "phtml", "php3", "php4", "php5", "php7", "phps", "php-s", "pht", "phar"
-            ]),
-        );
-        t.insert(
-            "typescript".to_string(),
-            Language {
-                extensions: osvec!["ts", "tsx"],
-                keep_first: None,
-                block_comment_start_pattern: Some(Regex::new(r"^\s*/\*").unwrap()),
-                block_comment_end_pattern: Some(Regex::new(r"\*/\s*$").unwrap()),
-                line_comment_start_pattern: Some(Regex::new(r"^\s*//").unwrap()),
-                line_comment_end_pattern: None,
-                header_start_line: Some(String::from("/*")),
-                header_end_line: Some(String::from(" */")),
-                header_line_prefix: Some(String::from(" * ")),
-                header_line_suffix: None,
-            },
-        );
-        t.insert(
-            "python".to_string(),
-            Language {
-                extensions: osvec!["py"],
-                keep_first: Some(
-                    Regex::new(r"^#!|^# +pylint|^# +-\*-|^# +coding|^# +encoding|^# +type|^# +flake8").unwrap(),
-                ),
-                block_comment_start_pattern: None,
-                block_comment_end_pattern: None,
-                line_comment_start_pattern: Some(Regex::new(r"^\s*#").unwrap()),
-                line_comment_end_pattern: None,
-                header_start_line: Some(String::from("#")),
-                header_end_line: Some(String::from("#")),
-                header_line_prefix: Some(String::from("# ")),
-                header_line_suffix: None,
-            },
-        );
-        t.insert("xml".to_string(), create_xml_style_language(osvec!["xml"]));
-        t.insert("svg".to_string(), create_xml_style_language(osvec!["svg"]));
-        t.insert("resx".to_string(), create_xml_style_language(osvec!["resx"]));
-        t.insert(
-            "proto".to_string(),
-            Language {
-                extensions: osvec!["proto"],
-                keep_first: None,
-                block_comment_start_pattern: None,
-                block_comment_end_pattern: None,
-                line_comment_start_pattern: Some(Regex::new(r"^\s*//").unwrap()),
-                line_comment_end_pattern: None,
-                header_start_line: None,
-                header_end_line: None,
-                header_line_prefix: Some(String::from("// ")),
-                header_line_suffix: None,
-            },
-        );
-        t.insert(
-            "html".to_string(),
-            Language {
-                extensions: osvec!["html"],
-                keep_first: Some(Regex::new(r"^\s*<!DOCTYPE.*>").unwrap()),
-                block_comment_start_pattern: Some(Regex::new(r"^\s*<!--").unwrap()),
-                block_comment_end_pattern: Some(Regex::new(r"-->\s*$").unwrap()),
-                line_comment_start_pattern: None,
-                line_comment_end_pattern: None,
-                header_start_line: Some(String::from("<!--")),
-                header_end_line: Some(String::from("-->")),
-                header_line_prefix: Some(String::from("   ")),
-                header_line_suffix: None,
-            },
-        );
-        t.insert(
-            "css".to_string(),
-            Language {
-                extensions: osvec!["css"],
-                keep_first: None,
-                block_comment_start_pattern: Some(Regex::new(r"^\s*/\*").unwrap()),
-                block_comment_end_pattern: Some(Regex::new(r"\*/\s*$").unwrap()),
-                line_comment_start_pattern: None,
-                line_comment_end_pattern: None,
-                header_start_line: Some(String::from("/*")),
-                header_end_line: Some(String::from("*/")),
-                header_line_prefix: Some(String::from(" * ")),
-                header_line_suffix: None,
-            },
-        );
-        t.insert(
-            "script".to_string(),
-            Language {
-                extensions: osvec!["sh", "csh", "pl"],
-                keep_first: Some(Regex::new(r"^#!|^# -\*-").unwrap()),
-                block_comment_start_pattern: None,
-                block_comment_end_pattern: None,
-                line_comment_start_pattern: Some(Regex::new(r"^\s*#").unwrap()),
-                line_comment_end_pattern: None,
-                header_start_line: Some(String::from("##")),
-                header_end_line: Some(String::from("##")),
-                header_line_prefix: Some(String::from("## ")),
-                header_line_suffix: None,
-            },
-        );
-        t
-    };
+            ])
+        },
+    );
+    t.insert(
+        "blade".to_string(),
+        Language {
+            extensions: osvec!["blade.php"],
+            block_comment_start_pattern: Some(Regex::new(r"^\s*\{\{--").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"--\}\}\s*$").unwrap()),
+            header_start_line: Some(String::from("{{--")),
+            header_end_line: Some(String::from("--}}")),
+            header_line_prefix: Some(String::from("    ")),
+            ..Default::default()
+        },
+    );
+    t.insert(
+        "erb".to_string(),
+        Language {
+            extensions: osvec!["erb", "html.erb", "js.erb", "text.erb"],
+            block_comment_start_pattern: Some(Regex::new(r"^\s*<%#").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"%>\s*$").unwrap()),
+            header_start_line: Some(String::from("<%#")),
+            header_end_line: Some(String::from("%>")),
+            header_line_prefix: Some(String::from("   ")),
+            ..Default::default()
+        },
+    );
+    t.insert("typescript".to_string(), create_c_style_language(osvec!["ts", "tsx"]));
+    t.insert(
+        "python".to_string(),
+        Language {
+            extensions: osvec!["py"],
+            file_names: osvec!["BUILD", "BUILD.bazel", "WORKSPACE", "SConstruct", "SConscript"],
+            interpreters: osvec!["python"],
+            keep_first: Some(
+                Regex::new(r"^#!|^# +pylint|^# +-\*-|^# +coding|^# +encoding|^# +type|^# +flake8").unwrap(),
+            ),
+            line_comment_start_pattern: Some(Regex::new(r"^\s*#").unwrap()),
+            header_start_line: Some(String::from("#")),
+            header_end_line: Some(String::from("#")),
+            header_line_prefix: Some(String::from("# ")),
+            ..Default::default()
+        },
+    );
+    t.insert("xml".to_string(), create_xml_style_language(osvec!["xml"]));
+    t.insert("svg".to_string(), create_xml_style_language(osvec!["svg"]));
+    t.insert("resx".to_string(), create_xml_style_language(osvec!["resx"]));
+    t.insert(
+        "proto".to_string(),
+        Language {
+            extensions: osvec!["proto"],
+            line_comment_start_pattern: Some(Regex::new(r"^\s*//").unwrap()),
+            header_line_prefix: Some(String::from("// ")),
+            ..Default::default()
+        },
+    );
+    t.insert(
+        "html".to_string(),
+        Language {
+            extensions: osvec!["html"],
+            preambles: vec![create_preamble(r"^\s*<!DOCTYPE", Some(r">\s*$"))],
+            block_comment_start_pattern: Some(Regex::new(r"^\s*<!--").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"-->\s*$").unwrap()),
+            header_start_line: Some(String::from("<!--")),
+            header_end_line: Some(String::from("-->")),
+            header_line_prefix: Some(String::from("   ")),
+            ..Default::default()
+        },
+    );
+    t.insert(
+        "css".to_string(),
+        Language {
+            extensions: osvec!["css"],
+            block_comment_start_pattern: Some(Regex::new(r"^\s*/\*").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\*/\s*$").unwrap()),
+            header_start_line: Some(String::from("/*")),
+            header_end_line: Some(String::from("*/")),
+            header_line_prefix: Some(String::from(" * ")),
+            ..Default::default()
+        },
+    );
+    t.insert(
+        "script".to_string(),
+        Language {
+            interpreters: osvec!["sh", "bash", "zsh", "ksh", "dash", "csh", "tcsh"],
+            keep_first: Some(Regex::new(r"^#!|^# -\*-").unwrap()),
+            ..create_hash_style_language(osvec!["sh", "csh"])
+        },
+    );
+    t.insert(
+        "perl".to_string(),
+        Language {
+            interpreters: osvec!["perl"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            ..create_hash_style_language(osvec!["pl", "pm", "t"])
+        },
+    );
+    t.insert(
+        "ruby".to_string(),
+        Language {
+            file_names: osvec!["Gemfile", "Rakefile", "Vagrantfile", "Podfile", "Guardfile", "Brewfile"],
+            interpreters: osvec!["ruby"],
+            // magic comments only work before any other comment
+            keep_first: Some(
+                Regex::new(
+                    r"^#!|^#\s*-\*-|^#\s*(frozen_string_literal|encoding|coding|warn_indent|shareable_constant_value)\s*:",
+                )
+                .unwrap(),
+            ),
+            ..create_hash_style_language(osvec!["rb", "rake", "gemspec", "ru"])
+        },
+    );
+    t.insert(
+        "r".to_string(),
+        Language {
+            interpreters: osvec!["Rscript"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            ..create_hash_style_language(osvec!["r", "R"])
+        },
+    );
+    t.insert(
+        "yaml".to_string(),
+        Language {
+            // directives and the document start marker stay in front of the header
+            keep_first: Some(Regex::new(r"^%YAML|^%TAG|^---\s*$").unwrap()),
+            ..create_hash_style_language(osvec!["yml", "yaml"])
+        },
+    );
+    t.insert("toml".to_string(), create_hash_style_language(osvec!["toml"]));
+    t.insert(
+        "cmake".to_string(),
+        Language {
+            file_names: osvec!["CMakeLists.txt"],
+            ..create_hash_style_language(osvec!["cmake"])
+        },
+    );
+    t.insert(
+        "makefile".to_string(),
+        Language {
+            file_names: osvec!["Makefile", "makefile", "GNUmakefile"],
+            ..create_hash_style_language(osvec!["mk", "mak"])
+        },
+    );
+    t.insert(
+        "dockerfile".to_string(),
+        Language {
+            file_names: osvec!["Dockerfile", "Containerfile"],
+            // parser directives are ignored after the first comment
+            keep_first: Some(Regex::new(r"^#\s*(syntax|escape|check)\s*=").unwrap()),
+            ..create_hash_style_language(osvec!["dockerfile", "containerfile"])
+        },
+    );
+    t.insert(
+        "terraform".to_string(),
+        Language {
+            block_comment_start_pattern: Some(Regex::new(r"^\s*/\*").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\*/\s*$").unwrap()),
+            line_comment_start_pattern: Some(Regex::new(r"^\s*(#|//)").unwrap()),
+            ..create_hash_style_language(osvec!["tf", "tfvars", "hcl"])
+        },
+    );
+    t.insert(
+        "nix".to_string(),
+        Language {
+            block_comment_start_pattern: Some(Regex::new(r"^\s*/\*").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\*/\s*$").unwrap()),
+            ..create_hash_style_language(osvec!["nix"])
+        },
+    );
+    t.insert(
+        "powershell".to_string(),
+        Language {
+            interpreters: osvec!["pwsh", "powershell"],
+            // changing a signed script would invalidate its signature
+            signature_pattern: Some(Regex::new(r"^# SIG # Begin signature block").unwrap()),
+            keep_first: Some(Regex::new(r"(?i)^#!|^#Requires\b").unwrap()),
+            // <# #> blocks with .SYNOPSIS and friends are comment-based help, the header goes above them
+            doc_comment_pattern: Some(Regex::new(r"^\s*<#").unwrap()),
+            doc_tag_pattern: Some(
+                Regex::new(r"(?i)^\s*\.(SYNOPSIS|DESCRIPTION|PARAMETER|EXAMPLE|NOTES|LINK)\b").unwrap(),
+            ),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*<#").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"#>\s*$").unwrap()),
+            ..create_hash_style_language(osvec!["ps1", "psm1", "psd1"])
+        },
+    );
+    t.insert(
+        "batch".to_string(),
+        Language {
+            extensions: osvec!["bat", "cmd"],
+            keep_first: Some(Regex::new(r"(?i)^\s*@echo\s+off\b").unwrap()),
+            line_comment_start_pattern: Some(Regex::new(r"(?i)^\s*(@?rem(\s|$)|::)").unwrap()),
+            // labels are never echoed, unlike REM before @echo off
+            header_start_line: Some(String::from("::")),
+            header_end_line: Some(String::from("::")),
+            header_line_prefix: Some(String::from(":: ")),
+            ..Default::default()
+        },
+    );
+    t.insert(
+        "sql".to_string(),
+        Language {
+            // migration tools read their markers from the first line
+            keep_first: Some(Regex::new(r"^--\s*liquibase formatted sql|^--\s*flyway:").unwrap()),
+            // Liquibase changeset directives are never part of the header
+            directive_pattern: Some(
+                Regex::new(r"^\s*--\s*(changeset\b|rollback\b|preconditions?\b|comment:|validCheckSum:|ignoreLines:)")
+                    .unwrap(),
+            ),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*/\*").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\*/\s*$").unwrap()),
+            ..create_double_dash_style_language(osvec!["sql", "psql", "plsql", "pls", "pks", "pkb", "ddl"])
+        },
+    );
+    t.insert(
+        "lua".to_string(),
+        Language {
+            interpreters: osvec!["lua", "luajit"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*--\[=*\[").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\]=*\]\s*$").unwrap()),
+            ..create_double_dash_style_language(osvec!["lua"])
+        },
+    );
+    t.insert(
+        "haskell".to_string(),
+        Language {
+            interpreters: osvec!["runhaskell", "runghc"],
+            // LANGUAGE and OPTIONS_GHC pragmas stay on top
+            keep_first: Some(Regex::new(r"^#!|^\{-#").unwrap()),
+            // Haddock comments document the module, the header goes above them
+            doc_comment_pattern: Some(Regex::new(r"^\s*(--\s*[|^$]|\{-\s*\|)").unwrap()),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*\{-([^#]|$)").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"-\}\s*$").unwrap()),
+            ..create_double_dash_style_language(osvec!["hs"])
+        },
+    );
+    t.insert(
+        "elisp".to_string(),
+        Language {
+            // the summary line and file variables, e.g. -*- lexical-binding: t -*-, have to stay the first line
+            keep_first: Some(Regex::new(r"^#!|^;.*-\*-.*-\*-|^;;;\s+\S+\.el\s+---").unwrap()),
+            // section headings of the library conventions are never part of the header
+            directive_pattern: Some(Regex::new(r"^;;;\s*(Commentary|Code|Change ?Log|History):").unwrap()),
+            header_start_line: Some(String::from(";;;")),
+            header_end_line: Some(String::from(";;;")),
+            header_line_prefix: Some(String::from(";;; ")),
+            ..create_semicolon_style_language(osvec!["el"])
+        },
+    );
+    t.insert(
+        "clojure".to_string(),
+        Language {
+            interpreters: osvec!["clojure", "bb"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            ..create_semicolon_style_language(osvec!["clj", "cljs", "cljc", "edn"])
+        },
+    );
+    t.insert(
+        "scheme".to_string(),
+        Language {
+            interpreters: osvec!["guile", "csi", "chez", "scheme"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*#\|").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\|#\s*$").unwrap()),
+            ..create_semicolon_style_language(osvec!["scm", "ss", "sld"])
+        },
+    );
+    t.insert(
+        "racket".to_string(),
+        Language {
+            interpreters: osvec!["racket"],
+            keep_first: Some(Regex::new(r"^#!|^#lang\b").unwrap()),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*#\|").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\|#\s*$").unwrap()),
+            ..create_semicolon_style_language(osvec!["rkt", "rktl"])
+        },
+    );
+    t.insert(
+        "asm".to_string(),
+        Language {
+            header_start_line: Some(String::from(";")),
+            header_end_line: Some(String::from(";")),
+            header_line_prefix: Some(String::from("; ")),
+            ..create_semicolon_style_language(osvec!["asm", "nasm", "s"])
+        },
+    );
+    t.insert(
+        "ini".to_string(),
+        Language {
+            file_names: osvec![".editorconfig"],
+            line_comment_start_pattern: Some(Regex::new(r"^\s*[;#]").unwrap()),
+            header_start_line: Some(String::from(";")),
+            header_end_line: Some(String::from(";")),
+            header_line_prefix: Some(String::from("; ")),
+            ..create_semicolon_style_language(osvec!["ini", "cfg"])
+        },
+    );
+    t.insert(
+        "ocaml".to_string(),
+        Language {
+            interpreters: osvec!["ocaml"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            // (** odoc comments document the module, the header goes above them
+            doc_comment_pattern: Some(Regex::new(r"^\s*\(\*\*([^*)]|$)").unwrap()),
+            ..create_ml_style_language(osvec!["ml", "mli", "mll", "mly"])
+        },
+    );
+    t.insert(
+        "fsharp".to_string(),
+        Language {
+            keep_first: Some(Regex::new(r"^#!|^#light\b").unwrap()),
+            doc_comment_pattern: Some(Regex::new(r"^\s*///([^/]|$)").unwrap()),
+            line_comment_start_pattern: Some(Regex::new(r"^\s*//").unwrap()),
+            ..create_ml_style_language(osvec!["fs", "fsi", "fsx"])
+        },
+    );
+    t.insert("sml".to_string(), create_ml_style_language(osvec!["sml"]));
+    t.insert(
+        "pascal".to_string(),
+        Language {
+            // .pp is Puppet too, Pascal files declare a unit, program, library or package
+            content_pattern: Some(Regex::new(r"(?mi)^\s*(unit|program|library)\b|^\s*package\s+[\w.]+\s*;").unwrap()),
+            // { } comments too, but not {$ } compiler directives
+            block_comment_start_pattern: Some(Regex::new(r"^\s*(\(\*|\{([^$]|$))").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"(\*\)|\})\s*$").unwrap()),
+            line_comment_start_pattern: Some(Regex::new(r"^\s*//").unwrap()),
+            ..create_ml_style_language(osvec!["pas", "pp", "dpr", "dpk", "lpr"])
+        },
+    );
+    t.insert(
+        "mathematica".to_string(),
+        Language {
+            interpreters: osvec!["wolframscript"],
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            ..create_ml_style_language(osvec!["wl", "wls"])
+        },
+    );
+    t.insert(
+        "erlang".to_string(),
+        Language {
+            file_names: osvec!["rebar.config"],
+            interpreters: osvec!["escript"],
+            // the -*- erlang -*- mode line and the %%! emulator flags of escripts stay on top
+            keep_first: Some(Regex::new(r"^#!|^%.*-\*-.*-\*-|^%%!").unwrap()),
+            header_start_line: Some(String::from("%%")),
+            header_end_line: Some(String::from("%%")),
+            header_line_prefix: Some(String::from("%% ")),
+            ..create_percent_style_language(osvec!["erl", "hrl", "escript"])
+        },
+    );
+    t.insert(
+        "elixir".to_string(),
+        Language {
+            interpreters: osvec!["elixir"],
+            keep_first: Some(Regex::new(r"^#!|^#\s*credo:").unwrap()),
+            ..create_hash_style_language(osvec!["ex", "exs"])
+        },
+    );
+    t.insert(
+        "matlab".to_string(),
+        Language {
+            interpreters: osvec!["octave"],
+            // .m is Objective-C too, MATLAB and Octave files have % comments, functions or classes
+            priority: 1,
+            content_pattern: Some(Regex::new(r"(?m)^\s*(%|function\b|classdef\b)").unwrap()),
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*%\{\s*$").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"^\s*%\}\s*$").unwrap()),
+            ..create_percent_style_language(osvec!["m"])
+        },
+    );
+    t.insert(
+        "latex".to_string(),
+        Language {
+            // TeXShop and VS Code read %!TEX magic comments from the first lines
+            keep_first: Some(Regex::new(r"^%\s*!T[eE]X").unwrap()),
+            ..create_percent_style_language(osvec!["tex", "ltx", "sty", "cls", "bib"])
+        },
+    );
+    t.insert(
+        "prolog".to_string(),
+        Language {
+            interpreters: osvec!["swipl"],
+            // .pl is Perl too, Prolog files have directives or % comments
+            priority: 1,
+            content_pattern: Some(Regex::new(r"(?m)^(:-|%(\s|$))").unwrap()),
+            keep_first: Some(Regex::new(r"^#!").unwrap()),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*/\*").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\*/\s*$").unwrap()),
+            ..create_percent_style_language(osvec!["pl", "pro", "prolog"])
+        },
+    );
+    t.insert(
+        "verilog".to_string(),
+        Language {
+            // compiler directives keep their position on top of the file
+            keep_first: Some(Regex::new(r"^\s*`(timescale|default_nettype)\b").unwrap()),
+            // tool pragmas in comments are never part of the header
+            directive_pattern: Some(
+                Regex::new(r"^\s*(//|/\*)\s*(synopsys|synthesis|pragma|verilator|cadence|altera|xilinx)\b").unwrap(),
+            ),
+            ..create_c_style_language(osvec!["v", "vh", "sv", "svh"])
+        },
+    );
+    t.insert(
+        "vhdl".to_string(),
+        Language {
+            // tool pragmas in comments are never part of the header
+            directive_pattern: Some(Regex::new(r"^\s*--\s*(synopsys|synthesis|pragma|altera|xilinx)\b").unwrap()),
+            ..create_double_dash_style_language(osvec!["vhd", "vhdl"])
+        },
+    );
+    t.insert(
+        "vue".to_string(),
+        Language {
+            keep_first: None,
+            ..create_xml_style_language(osvec!["vue"])
+        },
+    );
+    t.insert(
+        "svelte".to_string(),
+        Language {
+            keep_first: None,
+            ..create_xml_style_language(osvec!["svelte"])
+        },
+    );
+    t.insert(
+        "astro".to_string(),
+        Language {
+            // the header goes into the frontmatter when there is one, {/* */} is valid in the script and the markup
+            keep_first: Some(Regex::new(r"^---\s*$").unwrap()),
+            doc_comment_pattern: None,
+            doc_tag_pattern: None,
+            block_comment_start_pattern: Some(Regex::new(r"^\s*\{?/\*").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\*/\}?\s*$").unwrap()),
+            header_start_line: Some(String::from("{/*")),
+            header_end_line: Some(String::from(" */}")),
+            ..create_c_style_language(osvec!["astro"])
+        },
+    );
+    t.insert(
+        "razor".to_string(),
+        Language {
+            extensions: osvec!["cshtml", "razor"],
+            // @page has to be the first directive
+            keep_first: Some(
+                Regex::new(r"^\s*@(page|using|model|inherits|namespace|layout|implements|inject|attribute)\b").unwrap(),
+            ),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*@\*").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"\*@\s*$").unwrap()),
+            header_start_line: Some(String::from("@*")),
+            header_end_line: Some(String::from("*@")),
+            header_line_prefix: Some(String::from("   ")),
+            ..Default::default()
+        },
+    );
+    t.insert(
+        "jsp".to_string(),
+        Language {
+            extensions: osvec!["jsp", "jspf", "tag"],
+            keep_first: Some(Regex::new(r"^\s*<%@\s*(page|taglib|include)\b").unwrap()),
+            block_comment_start_pattern: Some(Regex::new(r"^\s*<%--").unwrap()),
+            block_comment_end_pattern: Some(Regex::new(r"--%>\s*$").unwrap()),
+            header_start_line: Some(String::from("<%--")),
+            header_end_line: Some(String::from("--%>")),
+            header_line_prefix: Some(String::from("    ")),
+            ..Default::default()
+        },
+    );
+    t.insert(
+        "markdown".to_string(),
+        Language {
+            keep_first: None,
+            // YAML or TOML front matter
+            preambles: vec![
+                create_preamble(r"^---\s*$", Some(r"^(---|\.\.\.)\s*$")),
+                create_preamble(r"^\+\+\+\s*$", Some(r"^\+\+\+\s*$")),
+            ],
+            // linter and table of contents markers are not headers
+            directive_pattern: Some(Regex::new(r"(?i)^\s*<!--\s*(markdownlint|prettier|vale|toc)\b").unwrap()),
+            ..create_xml_style_language(osvec!["md", "markdown"])
+        },
+    );
+    t.insert(
+        "rst".to_string(),
+        Language {
+            extensions: osvec!["rst"],
+            // directives, targets, substitutions, footnotes and citations share the .. markup with comments
+            directive_pattern: Some(Regex::new(r"^\.\.\s+(\S+::|_|\||\[)").unwrap()),
+            line_comment_start_pattern: Some(Regex::new(r"^\.\.(\s|$)").unwrap()),
+            header_line_prefix: Some(String::from(".. ")),
+            blank_line_after_header: true,
+            ..Default::default()
+        },
+    );
+    t
+}
+
+lazy_static! {
+    // compiling the patterns of all languages is expensive, so the table is only built once
+    static ref TYPE_SETTINGS: HashMap<String, Language> = built_in_languages();
+}
+
+fn get_type_settings(languages: &Option<Vec<&str>>) -> HashMap<&'static str, &'static Language> {
+    let type_settings = TYPE_SETTINGS.iter().map(|(key, value)| (key.as_str(), value));
     if let Some(lang_keys) = languages {
-        let mut filtered_type_settings: HashMap<String, Language> = HashMap::new();
-        for (key, value) in type_settings {
-            if lang_keys.contains(&key.as_str()) {
-                filtered_type_settings.insert(key, value);
-            }
-        }
+        let filtered_type_settings: HashMap<&'static str, &'static Language> =
+            type_settings.filter(|(key, _)| lang_keys.contains(key)).collect();
         if filtered_type_settings.is_empty() {
             log::error!(
                 "Specified languages {:?} are not supported, see help for more information.",
@@ -225,7 +820,7 @@ fn get_type_settings(languages: &Option<Vec<&str>>) -> HashMap<String, Language>
         }
         return filtered_type_settings;
     }
-    type_settings
+    type_settings.collect()
 }
 
 fn format_template(template: &[String], settings: &Language) -> Vec<String> {
@@ -235,13 +830,15 @@ fn format_template(template: &[String], settings: &Language) -> Vec<String> {
     }
     for line in template {
         let mut tmp: String = String::from(line);
-        if settings.header_line_prefix.is_some() && line.is_empty() {
-            tmp = format!("{}{}", settings.header_line_prefix.as_ref().unwrap().trim_end(), tmp);
-        } else if settings.header_line_prefix.is_some() {
-            tmp = format!("{}{}", settings.header_line_prefix.as_ref().unwrap(), tmp);
+        if let Some(prefix) = settings.header_line_prefix.as_ref() {
+            if line.is_empty() {
+                tmp = format!("{}{}", prefix.trim_end(), tmp);
+            } else {
+                tmp = format!("{}{}", prefix, tmp);
+            }
         }
-        if settings.header_line_suffix.is_some() {
-            tmp = format!("{}{}", tmp, settings.header_line_suffix.as_ref().unwrap())
+        if let Some(suffix) = settings.header_line_suffix.as_ref() {
+            tmp = format!("{}{}", tmp, suffix)
         }
         header.push(tmp);
     }
@@ -252,8 +849,7 @@ fn format_template(template: &[String], settings: &Language) -> Vec<String> {
     header
 }
 
-fn read_content(path: &Path) -> io::Result<Content> {
-    let buffer = read(path)?;
+fn parse_content(buffer: &[u8]) -> io::Result<Content> {
     let bom = Bom::from(&buffer[0..]);
     let mut bom_bytes: Option<Vec<u8>> = None;
     if bom != Bom::Null {
@@ -267,7 +863,6 @@ fn read_content(path: &Path) -> io::Result<Content> {
         ));
     }
 
-    log::debug!("Reading lines: {:?}", path);
     let mut raw_lines: Vec<Vec<u8>> = Vec::new();
     let mut lines: Vec<String> = Vec::new();
 
@@ -296,13 +891,55 @@ fn read_content(path: &Path) -> io::Result<Content> {
     Ok(content)
 }
 
+fn is_license_line(line: &str) -> bool {
+    LICENSE_PATTERN.is_match(line) || SPDX_PATTERN.is_match(line)
+}
+
+fn is_doc_comment(settings: &Language, line: &str) -> bool {
+    match settings.doc_comment_pattern.as_ref() {
+        Some(pattern) => pattern.is_match(line),
+        None => false,
+    }
+}
+
+// comments that are read by tools, e.g. pragmas or section markers, they are never part of a header
+fn is_directive(settings: &Language, line: &str) -> bool {
+    match settings.directive_pattern.as_ref() {
+        Some(pattern) => pattern.is_match(line),
+        None => false,
+    }
+}
+
+// the last line of a preamble starting at line i, the end is searched from the rest of the start line on
+fn preamble_end(settings: &Language, lines: &[String], i: usize) -> Option<usize> {
+    settings.preambles.iter().find_map(|preamble| {
+        let start = preamble.start_pattern.find(&lines[i])?;
+        let end_pattern = match preamble.end_pattern.as_ref() {
+            Some(pattern) => pattern,
+            None => return Some(i),
+        };
+        if end_pattern.is_match(&lines[i][start.end()..]) {
+            return Some(i);
+        }
+        for (j, line) in lines.iter().enumerate().skip(i + 1) {
+            if end_pattern.is_match(line) {
+                return Some(j);
+            }
+            if preamble.line_pattern.as_ref().is_some_and(|p| !p.is_match(line)) {
+                return None;
+            }
+        }
+        None
+    })
+}
+
 #[allow(clippy::if_same_then_else)]
-fn find_license(path: &Path, lang_type: &str, settings: &Language) -> Option<License> {
+fn find_license(path: &Path, buffer: &[u8], lang_type: &str, settings: &Language) -> Option<License> {
     let mut skip: usize = 0;
     let mut head_start = None;
     let mut years_line = None;
     let mut have_license = false;
-    let content = match read_content(path) {
+    let content = match parse_content(buffer) {
         Ok(c) => c,
         Err(why) => {
             log::error!("Could not read content from {:?}", path);
@@ -313,14 +950,33 @@ fn find_license(path: &Path, lang_type: &str, settings: &Language) -> Option<Lic
     let lines_count = content.lines.len();
     let mut is_block_header = false;
     let mut i: usize = 0;
-    for line in content.lines.iter() {
+    while i < lines_count {
+        let line = &content.lines[i];
         if (i == 0 || i == skip)
             && settings.keep_first.is_some()
             && settings.keep_first.as_ref().unwrap().is_match(line)
         {
             skip = i + 1;
+        } else if let Some(end) = preamble_end(settings, &content.lines, i).filter(|_| i == skip || skip > 0) {
+            // a preamble starts the file or follows the lines kept on top, empty lines in between are kept too
+            skip = end + 1;
+            i = end;
         } else if EMPTY_PATTERN.is_match(line) {
             // pass
+        } else if is_directive(settings, line) || (is_doc_comment(settings, line) && settings.doc_tag_pattern.is_none())
+        {
+            // documentation or directives before any header, the license goes above them separated by an empty line
+            log::debug!("Found doc comment or directive at line {}, no header in this file", i);
+            return Some(License {
+                language_type: lang_type.to_string(),
+                content,
+                skip,
+                head_start: Some(i),
+                head_end: None,
+                years_line: None,
+                settings: settings.clone(),
+                have_license,
+            });
         } else if settings.block_comment_start_pattern.is_some()
             && settings.block_comment_start_pattern.as_ref().unwrap().is_match(line)
         {
@@ -380,16 +1036,45 @@ fn find_license(path: &Path, lang_type: &str, settings: &Language) -> Option<Lic
     // otherwise process the comment block until it ends
     if is_block_header {
         log::debug!("Found comment start, process until end");
+        let is_doc_block = is_doc_comment(settings, &content.lines[i]);
+        let mut have_doc_tag = false;
         for j in i..lines_count {
             log::debug!("Checking line {}", j);
-            if LICENSE_PATTERN.is_match(&content.lines[j]) {
+            if is_doc_block
+                && settings
+                    .doc_tag_pattern
+                    .as_ref()
+                    .is_some_and(|p| p.is_match(&content.lines[j]))
+            {
+                have_doc_tag = true;
+            }
+            // a one line block comment both holds the license and ends the block
+            if is_license_line(&content.lines[j]) {
+                have_license = true;
+            } else if YEARS_PATTERN.is_match(&content.lines[j]) {
                 have_license = true;
-            } else if settings
+                years_line = Some(j);
+            }
+            if settings
                 .block_comment_end_pattern
                 .as_ref()
                 .unwrap()
                 .is_match(&content.lines[j])
             {
+                if have_doc_tag {
+                    // file documentation block, the license goes above it instead of replacing it
+                    log::debug!("Block comment at line {} is documentation, not a header", i);
+                    return Some(License {
+                        language_type: lang_type.to_string(),
+                        content,
+                        skip,
+                        head_start,
+                        head_end: None,
+                        years_line: None,
+                        settings: settings.clone(),
+                        have_license: false,
+                    });
+                }
                 return Some(License {
                     language_type: lang_type.to_string(),
                     content,
@@ -400,9 +1085,6 @@ fn find_license(path: &Path, lang_type: &str, settings: &Language) -> Option<Lic
                     settings: settings.clone(),
                     have_license,
                 });
-            } else if YEARS_PATTERN.is_match(&content.lines[j]) {
-                have_license = true;
-                years_line = Some(j);
             }
         }
 
@@ -427,7 +1109,9 @@ fn find_license(path: &Path, lang_type: &str, settings: &Language) -> Option<Lic
                 .as_ref()
                 .unwrap()
                 .is_match(&content.lines[j])
-                && LICENSE_PATTERN.is_match(&content.lines[j])
+                && !is_doc_comment(settings, &content.lines[j])
+                && !is_directive(settings, &content.lines[j])
+                && is_license_line(&content.lines[j])
             {
                 have_license = true;
             } else if !settings
@@ -435,6 +1119,8 @@ fn find_license(path: &Path, lang_type: &str, settings: &Language) -> Option<Lic
                 .as_ref()
                 .unwrap()
                 .is_match(&content.lines[j])
+                || is_doc_comment(settings, &content.lines[j])
+                || is_directive(settings, &content.lines[j])
             {
                 log::debug!("ELSE2");
                 return Some(License {
@@ -468,24 +1154,227 @@ fn find_license(path: &Path, lang_type: &str, settings: &Language) -> Option<Lic
     }
 }
 
+// the detected block has to be the template line by line, including the separator line ending it
 fn need_update(license: &License, template: &[String]) -> bool {
-    if license.have_license {
-        let start = license.head_start.unwrap_or_default();
-        for (i, line) in template.iter().enumerate() {
-            if line.ne(&license.content.lines[i + start]) {
-                return true;
-            }
+    let (Some(head_start), Some(head_end), true) = (license.head_start, license.head_end, license.have_license) else {
+        return true;
+    };
+    let existing = &license.content.lines[head_start..=head_end];
+    existing.len() != template.len()
+        || existing
+            .iter()
+            .zip(template)
+            .any(|(line, expected)| line.trim_end_matches(['\r', '\n']) != expected)
+}
+
+// header words without comment decoration, whitespace and case, (c) and © are the same word
+fn normalize_header(lines: &[String]) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for line in lines {
+        let text = strip_decoration(line).to_lowercase();
+        let text = COPYRIGHT_SIGN_PATTERN.replace_all(&text, " (c) ");
+        words.extend(text.split_whitespace().map(String::from));
+    }
+    words
+}
+
+// ratio of the words in the longest common subsequence of both headers
+fn similarity(a: &[String], b: &[String]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let mut previous = vec![0usize; b.len() + 1];
+    for word in a {
+        let mut current = vec![0usize; b.len() + 1];
+        for (j, other) in b.iter().enumerate() {
+            current[j + 1] = if word == other {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
         }
-        return false;
+        previous = current;
     }
-    true
+    2.0 * previous[b.len()] as f64 / (a.len() + b.len()) as f64
 }
 
-fn copywrite_file(path: &Path, lang_type: &str, settings: &Language, template: &[String]) {
-    let license: License = match find_license(path, lang_type, settings) {
-        Some(l) => l,
-        None => return,
+// how an existing header compares to the template when neither is taken word for word
+#[derive(Debug, PartialEq)]
+enum Likeness {
+    // the same words, only formatting differs
+    Cosmetic,
+    // different words, but within the similarity threshold
+    Similar,
+    Different,
+}
+
+// the years have to be the same for a header to be kept
+fn likeness(license: &License, template: &[String], threshold: f64) -> Likeness {
+    let (Some(head_start), Some(head_end), true) = (license.head_start, license.head_end, license.have_license) else {
+        return Likeness::Different;
+    };
+    let existing = &license.content.lines[head_start..=head_end];
+    let years = |lines: &[String]| -> Vec<String> {
+        lines
+            .iter()
+            .flat_map(|line| {
+                YEARS_PATTERN
+                    .captures_iter(line)
+                    .map(|c| c[2].to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
     };
+    if years(existing) != years(template) {
+        return Likeness::Different;
+    }
+    let (existing, template) = (normalize_header(existing), normalize_header(template));
+    if existing == template {
+        return Likeness::Cosmetic;
+    }
+    let ratio = similarity(&existing, &template);
+    log::debug!("Header similarity {:.3}", ratio);
+    if ratio >= threshold {
+        Likeness::Similar
+    } else {
+        Likeness::Different
+    }
+}
+
+fn is_third_party(license: &License, owner: Option<&Regex>) -> bool {
+    match (owner, license.have_license, license.head_start, license.head_end) {
+        // a header that also holds the copyright of others isn't ours to replace
+        (Some(owner), true, Some(start), Some(end)) => {
+            !license.content.lines[start..=end]
+                .iter()
+                .any(|line| owner.is_match(line))
+                || find_owner_line(license, owner).is_some()
+        }
+        _ => false,
+    }
+}
+
+// index of our copyright line in a header that also holds copyright lines of others
+fn find_owner_line(license: &License, owner: &Regex) -> Option<usize> {
+    let (start, end) = (license.head_start?, license.head_end?);
+    let lines = &license.content.lines[start..=end];
+    let ours = lines.iter().position(|line| owner.is_match(line))?;
+    if lines
+        .iter()
+        .any(|line| YEARS_PATTERN.is_match(line) && !owner.is_match(line))
+    {
+        return Some(start + ours);
+    }
+    None
+}
+
+// comment decoration in front of the copyright text, e.g. " * " or "# "
+fn line_prefix(line: &str) -> &str {
+    let end = line
+        .find(|c: char| c.is_alphanumeric() || c == '(' || c == '©')
+        .unwrap_or(line.len());
+    &line[..end]
+}
+
+// the line ending of the file, new lines are written the same way
+fn line_ending(content: &Content) -> &'static [u8] {
+    match content.raw_lines.iter().find(|line| line.ends_with(b"\n")) {
+        Some(line) if line.ends_with(b"\r\n") => b"\r\n",
+        _ => b"\n",
+    }
+}
+
+fn splice(content: &Content, range: Range<usize>, lines: &[String]) -> Vec<u8> {
+    let eol = line_ending(content);
+    let mut buffer: Vec<u8> = Vec::new();
+    // if bom was found make sure to write it back
+    if let Some(bom_bytes) = content.bom_bytes.as_ref() {
+        buffer.extend_from_slice(bom_bytes);
+    }
+    // first the lines before the change
+    for raw_line in &content.raw_lines[0..range.start] {
+        buffer.extend_from_slice(raw_line);
+    }
+    // now the new lines
+    for line in lines {
+        buffer.extend_from_slice(line.as_bytes());
+        buffer.extend_from_slice(eol);
+    }
+    // now the rest of the lines
+    for raw_line in &content.raw_lines[range.end..] {
+        buffer.extend_from_slice(raw_line);
+    }
+    buffer
+}
+
+fn write_file(path: &Path, buffer: &[u8]) {
+    match File::create(path) {
+        Ok(mut file) => file.write_all(buffer).expect("Can't write to file"),
+        Err(why) => {
+            log::error!("Can't create file {:?}", path);
+            log::error!("{}", why);
+        }
+    };
+}
+
+fn append_owner_line(path: &Path, license: &License, owner: &Regex, owner_line: &str) -> Option<Vec<u8>> {
+    let is_block_header = license.head_start.is_some_and(|start| {
+        license
+            .settings
+            .block_comment_start_pattern
+            .as_ref()
+            .is_some_and(|p| p.is_match(&license.content.lines[start]))
+    });
+    if let Some(ours) = find_owner_line(license, owner) {
+        let line = format!("{}{}", line_prefix(&license.content.lines[ours]), owner_line);
+        if license.content.lines[ours].trim_end() == line.trim_end() {
+            log::info!("Copyright line is up-to-date in file {:?}", path);
+            return Some(splice(&license.content, 0..0, &[]));
+        }
+        log::info!("Updating copyright line in file {:?}", path);
+        return Some(splice(&license.content, ours..ours + 1, &[line]));
+    }
+    // the last copyright line, also when it carries the license text and years_line isn't set
+    let last_copyright = license.head_start.zip(license.head_end).and_then(|(start, end)| {
+        (start..=end)
+            .rev()
+            .find(|&i| copyright(&license.content.lines[i]).is_some())
+    });
+    match last_copyright {
+        // a copyright line that also closes the block comment has no room after it
+        Some(line_index) if !(is_block_header && license.head_end == Some(line_index)) => {
+            let prefix = if is_block_header && license.head_start == Some(line_index) {
+                license.settings.header_line_prefix.as_deref().unwrap_or_default()
+            } else {
+                line_prefix(&license.content.lines[line_index])
+            };
+            let line = format!("{}{}", prefix, owner_line);
+            log::info!("Appending copyright line to third-party header in file {:?}", path);
+            Some(splice(&license.content, line_index + 1..line_index + 1, &[line]))
+        }
+        _ => None,
+    }
+}
+
+fn check_spdx(path: &Path, license: &License) {
+    if let (Some(start), Some(end)) = (license.head_start, license.head_end) {
+        for line in &license.content.lines[start..=end] {
+            if let Some(captures) = SPDX_ID_PATTERN.captures(line) {
+                if let Err(why) = validate_expression(&captures[1]) {
+                    log::warn!("Invalid SPDX license expression {:?} in file {:?}, {}", &captures[1], path, why);
+                }
+            }
+        }
+    }
+}
+
+// the file content with the header added or updated
+#[allow(clippy::too_many_arguments)]
+fn transform(
+    path: &Path, buffer: &[u8], lang_type: &str, settings: &Language, template: &[String], options: &Options,
+    owner_line: Option<&str>, report: &mut Report,
+) -> Option<Vec<u8>> {
+    let license: License = find_license(path, buffer, lang_type, settings)?;
     log::debug!(
         "Info for the file: head_start={:?}, head_end={:?}, have_license={}, skip={}, len={}, years_line={:?}",
         license.head_start,
@@ -495,71 +1384,609 @@ fn copywrite_file(path: &Path, lang_type: &str, settings: &Language, template: &
         license.content.lines.len(),
         license.years_line
     );
-    if !need_update(&license, template) {
+    if let Some(pattern) = settings.signature_pattern.as_ref() {
+        if license.content.lines.iter().any(|line| pattern.is_match(line)) {
+            log::warn!("Signed file {:?}, skipping.", path);
+            return Some(buffer.to_vec());
+        }
+    }
+    check_spdx(path, &license);
+    if let (Some(owner), Some(owner_line), ThirdParty::Append) =
+        (options.owner.as_ref(), owner_line, options.third_party)
+    {
+        if license.have_license && is_third_party(&license, Some(owner)) {
+            return match append_owner_line(path, &license, owner, owner_line) {
+                Some(content) => Some(content),
+                None => {
+                    log::warn!("No copyright line to append to in file {:?}, skipping.", path);
+                    Some(buffer.to_vec())
+                }
+            };
+        }
+    }
+    let third_party = is_third_party(&license, options.owner.as_ref());
+    if third_party && options.third_party == ThirdParty::Skip {
+        log::warn!("Third-party header found in file {:?}, skipping.", path);
+        return Some(buffer.to_vec());
+    }
+    if !third_party && !need_update(&license, template) {
         log::info!("Header is up-to-date in file {:?}", path);
-        return;
+        return Some(buffer.to_vec());
     }
-    match File::create(path) {
-        Ok(mut file) => {
-            // if bom was found make sure to write it back
-            if let Some(bom_bytes) = license.content.bom_bytes {
-                file.write_all(&bom_bytes).expect("Can't write BOM to file");
+    if let (Some(threshold), false) = (options.similarity, third_party) {
+        match likeness(&license, template, threshold) {
+            Likeness::Cosmetic => {
+                log::info!("Header only differs cosmetically in file {:?}, skipping.", path);
+                report.cosmetic.push(path.to_path_buf());
+                return Some(buffer.to_vec());
             }
-            if license.head_start.is_some() && license.head_end.is_some() && license.have_license {
-                log::info!("Replacing header in file {:?}", path);
-                let head_start = license.head_start.unwrap();
-                let head_end = license.head_end.unwrap();
-                // first write the lines before the header
-                for raw_line in &license.content.raw_lines[0..head_start] {
-                    file.write_all(raw_line).expect("Can't write header to file");
-                }
-                // now write the new header from the template lines
-                for line in template {
-                    writeln!(file, "{}", line).expect("Can't write template to file");
-                }
-                // now write the rest of the lines
-                for raw_line in &license.content.raw_lines[head_end + 1..] {
-                    file.write_all(raw_line).expect("Can't write body to file");
-                }
-            } else {
-                log::info!("Adding header to file {:?}", path);
-                let skip = license.skip;
-                for raw_line in &license.content.raw_lines[0..skip] {
-                    file.write_all(raw_line).expect("Can't write header to file");
-                }
-                for line in template {
-                    writeln!(file, "{}", line).expect("Can't write template to file");
-                }
-                if license.head_start.is_some() && !license.have_license {
-                    // there is some header, but not license - add an empty line
-                    writeln!(file).expect("Can't write empty line");
-                }
-                for raw_line in &license.content.raw_lines[skip..] {
-                    file.write_all(raw_line).expect("Can't write body to file");
-                }
+            Likeness::Similar => {
+                log::info!("Header is similar to the template in file {:?}, skipping.", path);
+                report.similar.push(path.to_path_buf());
+                return Some(buffer.to_vec());
             }
+            Likeness::Different => {}
+        }
+    }
+    if let (Some(head_start), Some(head_end), true, false) =
+        (license.head_start, license.head_end, license.have_license, third_party)
+    {
+        log::info!("Replacing header in file {:?}", path);
+        let mut header = template.to_vec();
+        if needs_blank_line(&license, head_end + 1) {
+            header.push(String::new());
         }
+        Some(splice(&license.content, head_start..head_end + 1, &header))
+    } else {
+        if third_party {
+            log::info!("Adding header above third-party header in file {:?}", path);
+        } else {
+            log::info!("Adding header to file {:?}", path);
+        }
+        let mut header = template.to_vec();
+        if (license.head_start.is_some() && (!license.have_license || third_party))
+            || needs_blank_line(&license, license.skip)
+        {
+            // there is some header, but not our license - add an empty line
+            header.push(String::new());
+        }
+        Some(splice(&license.content, license.skip..license.skip, &header))
+    }
+}
+
+// the line following the header isn't empty although the language needs it to be
+fn needs_blank_line(license: &License, next: usize) -> bool {
+    license.settings.blank_line_after_header
+        && license
+            .content
+            .lines
+            .get(next)
+            .is_some_and(|line| !EMPTY_PATTERN.is_match(line))
+}
+
+fn copywrite_file(
+    path: &Path, lang_type: &str, settings: &Language, template: &[String], options: &Options,
+    owner_line: Option<&str>, report: &mut Report,
+) {
+    let buffer = match read(path) {
+        Ok(b) => b,
         Err(why) => {
-            log::error!("Can't create file {:?}", path);
+            log::error!("Could not read content from {:?}", path);
             log::error!("{}", why);
+            return;
+        }
+    };
+    if let Some(content) = transform(path, &buffer, lang_type, settings, template, options, owner_line, report) {
+        if content != buffer {
+            write_file(path, &content);
         }
+    }
+}
+
+// the content without our header and the empty lines separating it from the rest
+fn body(license: &License) -> Vec<u8> {
+    let (start, mut end) = match (license.head_start, license.head_end, license.have_license) {
+        (Some(head_start), Some(head_end), true) => (head_start, head_end + 1),
+        _ => (license.skip, license.skip),
+    };
+    while end < license.content.lines.len() && EMPTY_PATTERN.is_match(&license.content.lines[end]) {
+        end += 1;
+    }
+    splice(&license.content, start..end, &[])
+}
+
+fn verify_file(
+    path: &Path, lang_type: &str, settings: &Language, template: &[String], options: &Options, owner_line: Option<&str>,
+) -> Option<String> {
+    let buffer = match read(path) {
+        Ok(b) => b,
+        Err(why) => return Some(format!("could not read the file, {}", why)),
+    };
+    let mut report = Report::default();
+    let first = transform(path, &buffer, lang_type, settings, template, options, owner_line, &mut report)?;
+    let second = transform(path, &first, lang_type, settings, template, options, owner_line, &mut report)?;
+    if second != first {
+        return Some(String::from("a second run would change the file again"));
+    }
+    let original = find_license(path, &buffer, lang_type, settings)?;
+    if original.have_license && is_third_party(&original, options.owner.as_ref()) {
+        // removing our header doesn't give back a third-party header
+        return None;
+    }
+    let updated = find_license(path, &first, lang_type, settings)?;
+    if body(&updated) != body(&original) {
+        return Some(String::from("removing the header would not restore the original content"));
+    }
+    None
+}
+
+// only the top of the file is checked, declarations that tell languages apart come early
+const CONTENT_LINES: usize = 100;
+
+fn matches_content(path: &Path, pattern: &Regex) -> bool {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return false,
     };
+    let mut reader = io::BufReader::new(file);
+    let mut buffer: Vec<u8> = Vec::new();
+    for _ in 0..CONTENT_LINES {
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    pattern.is_match(&String::from_utf8_lossy(&buffer))
+}
+
+fn read_interpreter(path: &Path) -> Option<OsString> {
+    let mut first_line: Vec<u8> = Vec::new();
+    let file = File::open(path).ok()?;
+    io::BufReader::new(file)
+        .take(256)
+        .read_until(b'\n', &mut first_line)
+        .ok()?;
+    if !first_line.starts_with(b"#!") {
+        return None;
+    }
+    let shebang = String::from_utf8_lossy(&first_line[2..]).to_string();
+    let mut args = shebang.split_whitespace();
+    let mut program = Path::new(args.next()?).file_name()?.to_string_lossy().to_string();
+    if program == "env" {
+        // skip env options and variable assignments, e.g. #!/usr/bin/env -S VAR=1 python3 -u
+        program = args.find(|a| !a.starts_with('-') && !a.contains('='))?.to_string();
+    }
+    // python3.11 -> python
+    Some(OsString::from(
+        program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'),
+    ))
+}
+
+fn matches_suffix(file_name: &str, suffix: &str) -> bool {
+    if let Some(extension) = suffix.strip_prefix('.') {
+        return matches_extension(file_name, extension);
+    }
+    file_name == suffix
+}
+
+fn matches_extension(file_name: &str, extension: &str) -> bool {
+    file_name.len() > extension.len() + 1
+        && file_name.ends_with(extension)
+        && file_name.as_bytes()[file_name.len() - extension.len() - 1] == b'.'
+}
+
+// the language of the longest matching suffix in the mappings
+fn mapped_language<'a>(path: &Path, mappings: &'a [(String, String)]) -> Option<&'a str> {
+    let file_name = path.file_name()?.to_string_lossy();
+    mappings
+        .iter()
+        .filter(|(suffix, _)| matches_suffix(&file_name, suffix))
+        .max_by_key(|(suffix, _)| suffix.len())
+        .map(|(_, lang_type)| lang_type.as_str())
+}
+
+fn detect_language(
+    path: &Path, type_settings: &HashMap<&'static str, &'static Language>, mappings: &[(String, String)],
+) -> Option<(&'static str, &'static Language)> {
+    let file_name = path.file_name()?.to_string_lossy();
+    // a HashMap has no stable order, go through the languages by name so the result doesn't change between runs
+    let mut languages: Vec<(&'static str, &'static Language)> = type_settings.iter().map(|(k, v)| (*k, *v)).collect();
+    languages.sort_by_key(|(lang_type, _)| *lang_type);
+
+    // explicit mappings always win
+    if let Some(lang_type) = mapped_language(path, mappings) {
+        log::debug!("{:?} is mapped to {}", path, lang_type);
+        return type_settings.get_key_value(lang_type).map(|(k, v)| (*k, *v));
+    }
+
+    for (lang_type, settings) in languages.iter().copied() {
+        if settings.file_names.iter().any(|x| x == file_name.as_ref()) {
+            return Some((lang_type, settings));
+        }
+    }
+
+    // longest matching extension wins, ties are resolved by priority and then by name
+    let mut candidates: Vec<(usize, u8, &'static str, &'static Language)> = Vec::new();
+    for (lang_type, settings) in languages.iter().copied() {
+        if let Some(length) = settings
+            .extensions
+            .iter()
+            .map(|x| x.to_string_lossy())
+            .filter(|x| matches_extension(&file_name, x))
+            .map(|x| x.len())
+            .max()
+        {
+            // extensions shared with other languages, e.g. .m, are only ours when the content looks like it
+            if let Some(pattern) = settings.content_pattern.as_ref() {
+                if !matches_content(path, pattern) {
+                    continue;
+                }
+            }
+            candidates.push((length, settings.priority, lang_type, settings));
+        }
+    }
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
+    if let Some((_, _, lang_type, settings)) = candidates.first() {
+        return Some((lang_type, settings));
+    }
+
+    // only scripts without an extension are detected by their interpreter, not e.g. .git/hooks/*.sample
+    if path.extension().is_some() {
+        return None;
+    }
+    if let Some(interpreter) = read_interpreter(path) {
+        log::debug!("Found interpreter {:?} in {:?}", interpreter, path);
+        for (lang_type, settings) in languages.iter().copied() {
+            if settings.interpreters.contains(&interpreter) {
+                return Some((lang_type, settings));
+            }
+        }
+    }
+    None
+}
+
+fn check_mappings(mappings: &[(String, String)], known_languages: &HashMap<&'static str, &'static Language>) {
+    for (suffix, lang_type) in mappings {
+        if lang_type != IGNORE_LANGUAGE && !known_languages.contains_key(lang_type.as_str()) {
+            log::error!(
+                "Mapping {}={} refers to an unknown language, see help for more information.",
+                suffix,
+                lang_type
+            );
+            exit(11);
+        }
+    }
+}
+
+// our copyright line, appended to third-party headers in append mode
+fn owner_line<'a>(template: &'a [String], options: &Options) -> Option<&'a str> {
+    options
+        .owner
+        .as_ref()
+        .map(|owner| match template.iter().find(|line| owner.is_match(line)) {
+            Some(line) => line.as_str(),
+            None => {
+                // without this our own header would be considered third-party on the next run
+                log::error!("Template doesn't match the owner pattern {:?}", owner.as_str());
+                exit(12);
+            }
+        })
 }
 
-pub fn copywrite_path(files: &[OsString], template: &[String], languages: &Option<Vec<&str>>) {
+pub fn copywrite_path(
+    files: &[OsString], template: &[String], languages: &Option<Vec<&str>>, options: &Options,
+) -> Report {
     let type_settings = get_type_settings(languages);
+    let known_languages = get_type_settings(&None);
+    check_mappings(&options.mappings, &known_languages);
+    let owner_line = owner_line(template, options);
 
+    let mut report = Report::default();
     for file in files {
         let file_path = PathBuf::from(file);
-        if let Some(extension) = file_path.extension() {
-            for (lang_type, settings) in type_settings.iter() {
-                if settings.extensions.iter().any(|x| x == extension) {
-                    log::debug!("Checking file {:?}", file_path);
-                    let header = format_template(template, settings);
-                    copywrite_file(&file_path, lang_type, settings, &header);
+        if options.reuse && reuse::is_ignored(&file_path) {
+            log::debug!("{:?} is ignored by REUSE, skipping.", file_path);
+        } else if let Some((lang_type, settings)) = detect_language(&file_path, &type_settings, &options.mappings) {
+            log::debug!("Checking file {:?} as {}", file_path, lang_type);
+            let header = format_template(template, settings);
+            copywrite_file(&file_path, lang_type, settings, &header, options, owner_line, &mut report);
+            report.checked.push(file_path);
+        } else if options.reuse
+            && mapped_language(&file_path, &options.mappings) != Some(IGNORE_LANGUAGE)
+            && detect_language(&file_path, &known_languages, &[]).is_none()
+        {
+            // files we can't comment get the header in a <file>.license sidecar
+            reuse::write_sidecar(&file_path, template);
+            report.sidecars.push(reuse::sidecar_path(&file_path));
+            report.checked.push(file_path);
+        }
+    }
+    report
+}
+
+pub fn verify_path(
+    files: &[OsString], template: &[String], languages: &Option<Vec<&str>>, options: &Options,
+) -> Vec<Failure> {
+    let type_settings = get_type_settings(languages);
+    check_mappings(&options.mappings, &get_type_settings(&None));
+    let owner_line = owner_line(template, options);
+
+    let mut failures: Vec<Failure> = Vec::new();
+    for file in files {
+        let file_path = PathBuf::from(file);
+        if let Some((lang_type, settings)) = detect_language(&file_path, &type_settings, &options.mappings) {
+            log::debug!("Verifying file {:?} as {}", file_path, lang_type);
+            let header = format_template(template, settings);
+            if let Some(reason) = verify_file(&file_path, lang_type, settings, &header, options, owner_line) {
+                failures.push(Failure {
+                    path: file_path,
+                    reason,
+                });
+            }
+        }
+    }
+    failures
+}
+
+// the lines of all headers in the comment blocks on top, our header can sit above a third-party one
+fn header_lines(path: &Path, buffer: &[u8], lang_type: &str, settings: &Language) -> Option<Vec<String>> {
+    let mut license = find_license(path, buffer, lang_type, settings)?;
+    let mut lines: Vec<String> = Vec::new();
+    while let (Some(start), Some(end)) = (license.head_start, license.head_end) {
+        if license.have_license {
+            lines.extend(
+                license.content.lines[start..=end]
+                    .iter()
+                    .map(|line| line.trim_end().to_string()),
+            );
+        }
+        let rest = license.content.raw_lines[end + 1..].concat();
+        license = match find_license(path, &rest, lang_type, settings) {
+            Some(l) => l,
+            None => break,
+        };
+    }
+    Some(lines)
+}
+
+pub fn scan_path(files: &[OsString], languages: &Option<Vec<&str>>, mappings: &[(String, String)]) -> Vec<Header> {
+    let type_settings = get_type_settings(languages);
+    check_mappings(mappings, &get_type_settings(&None));
+
+    let mut headers: Vec<Header> = Vec::new();
+    for file in files {
+        let file_path = PathBuf::from(file);
+        if reuse::is_ignored(&file_path) {
+            continue;
+        }
+        if let Some((lang_type, settings)) = detect_language(&file_path, &type_settings, mappings) {
+            log::debug!("Scanning file {:?} as {}", file_path, lang_type);
+            let lines = match read(&file_path) {
+                Ok(buffer) => match header_lines(&file_path, &buffer, lang_type, settings) {
+                    Some(l) => l,
+                    None => continue,
+                },
+                Err(why) => {
+                    log::error!("Could not read content from {:?}", file_path);
+                    log::error!("{}", why);
                     continue;
                 }
-            }
+            };
+            headers.push(Header {
+                path: file_path,
+                language: lang_type.to_string(),
+                lines,
+            });
+        } else if let Ok(text) = read_to_string(reuse::sidecar_path(&file_path)) {
+            log::debug!("Scanning license file of {:?}", file_path);
+            headers.push(Header {
+                path: file_path,
+                language: String::from("license"),
+                lines: text.lines().map(|line| line.to_string()).collect(),
+            });
         }
     }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> Vec<String> {
+        let settings = &get_type_settings(&None)["c"];
+        let lines = vec![
+            String::from("Copyright (c) 2024 Acme"),
+            String::from("Licensed under MIT."),
+            String::new(),
+        ];
+        format_template(&lines, settings)
+    }
+
+    fn license(lines: &[&str], head_start: Option<usize>, head_end: Option<usize>) -> License {
+        let lines: Vec<String> = lines.iter().map(|line| format!("{}\n", line)).collect();
+        License {
+            language_type: String::from("c"),
+            content: Content {
+                bom: Bom::Null,
+                bom_bytes: None,
+                raw_lines: lines.iter().map(|line| line.as_bytes().to_vec()).collect(),
+                lines,
+            },
+            skip: 0,
+            head_start,
+            head_end,
+            years_line: Some(1),
+            settings: TYPE_SETTINGS["c"].clone(),
+            have_license: true,
+        }
+    }
+
+    #[test]
+    fn identical_header_is_up_to_date() {
+        let lines = [
+            "/*",
+            " * Copyright (c) 2024 Acme",
+            " * Licensed under MIT.",
+            " *",
+            " */",
+            "int x;",
+        ];
+        assert!(!need_update(&license(&lines, Some(0), Some(4)), &template()));
+    }
+
+    #[test]
+    fn crlf_header_is_up_to_date() {
+        let lines = [
+            "/*\r",
+            " * Copyright (c) 2024 Acme\r",
+            " * Licensed under MIT.\r",
+            " *\r",
+            " */\r",
+        ];
+        assert!(!need_update(&license(&lines, Some(0), Some(4)), &template()));
+    }
+
+    #[test]
+    fn longer_header_is_stale() {
+        let lines = [
+            "/*",
+            " * Copyright (c) 2024 Acme",
+            " * Licensed under MIT.",
+            " *",
+            " * See LICENSE for details.",
+            " */",
+            "int x;",
+        ];
+        assert!(need_update(&license(&lines, Some(0), Some(5)), &template()));
+    }
+
+    #[test]
+    fn header_without_separator_is_stale() {
+        let lines = [
+            "/*",
+            " * Copyright (c) 2024 Acme",
+            " * Licensed under MIT.",
+            " */",
+            "int x;",
+        ];
+        assert!(need_update(&license(&lines, Some(0), Some(3)), &template()));
+    }
+
+    #[test]
+    fn shorter_header_at_end_of_file_is_stale() {
+        let lines = ["/*", " * Copyright (c) 2024 Acme", " */"];
+        assert!(need_update(&license(&lines, Some(0), Some(2)), &template()));
+    }
+
+    #[test]
+    fn header_after_kept_lines_is_compared_from_its_start() {
+        let lines = [
+            "",
+            "/*",
+            " * Copyright (c) 2024 Acme",
+            " * Licensed under MIT.",
+            " *",
+            " */",
+        ];
+        assert!(!need_update(&license(&lines, Some(1), Some(5)), &template()));
+    }
+
+    #[test]
+    fn missing_license_needs_update() {
+        let lines = [
+            "/*",
+            " * Copyright (c) 2024 Acme",
+            " * Licensed under MIT.",
+            " *",
+            " */",
+        ];
+        let mut license = license(&lines, Some(0), Some(4));
+        license.have_license = false;
+        assert!(need_update(&license, &template()));
+        license.have_license = true;
+        license.head_end = None;
+        assert!(need_update(&license, &template()));
+    }
+
+    #[test]
+    fn header_files_are_c_unless_narrowed_down() {
+        let path = Path::new("include/api.h");
+        let all = get_type_settings(&None);
+        assert_eq!(detect_language(path, &all, &[]).map(|(lang_type, _)| lang_type), Some("c"));
+        let cpp = get_type_settings(&Some(vec!["cpp"]));
+        assert_eq!(detect_language(path, &cpp, &[]).map(|(lang_type, _)| lang_type), Some("cpp"));
+    }
+
+    #[test]
+    fn header_with_other_copyrights_is_third_party() {
+        let owner = Regex::new("Acme").unwrap();
+        let ours = ["/*", " * Copyright (c) 2024 Acme", " */"];
+        assert!(!is_third_party(&license(&ours, Some(0), Some(2)), Some(&owner)));
+        let mixed = [
+            "/*",
+            " * Copyright (c) 2015 Upstream Authors",
+            " * Copyright (c) 2024 Acme",
+            " */",
+        ];
+        assert!(is_third_party(&license(&mixed, Some(0), Some(3)), Some(&owner)));
+    }
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn normalized_headers_ignore_decoration_case_and_copyright_sign() {
+        let lines: Vec<String> = ["/*", " * COPYRIGHT © 2024  Acme", " *\tLicensed under MIT.", " */"]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(normalize_header(&lines), words("copyright (c) 2024 acme licensed under mit."));
+        assert_eq!(normalize_header(&template()), normalize_header(&lines));
+    }
+
+    #[test]
+    fn similarity_is_the_share_of_common_words() {
+        let header = words("copyright (c) 2024 acme");
+        assert_eq!(similarity(&header, &header), 1.0);
+        assert_eq!(similarity(&[], &[]), 1.0);
+        assert_eq!(similarity(&header, &words("all rights reserved")), 0.0);
+        assert_eq!(similarity(&header, &words("copyright (c) 2024 acme inc")), 8.0 / 9.0);
+        assert_eq!(similarity(&words("a b c d"), &words("d c b a")), 2.0 / 8.0);
+    }
+
+    #[test]
+    fn reworded_headers_are_similar_not_cosmetic() {
+        let cosmetic = ["/*", " * COPYRIGHT © 2024 Acme", " *   Licensed under MIT.", " */"];
+        assert_eq!(
+            likeness(&license(&cosmetic, Some(0), Some(3)), &template(), 0.5),
+            Likeness::Cosmetic
+        );
+        let renamed = ["/*", " * Copyright (c) 2024 Acme Inc", " * Licensed under MIT.", " */"];
+        assert_eq!(
+            likeness(&license(&renamed, Some(0), Some(3)), &template(), 0.5),
+            Likeness::Similar
+        );
+        let proprietary = [
+            "/*",
+            " * Copyright (c) 2024 Acme",
+            " * Licensed under MIT.",
+            " * Proprietary, do not distribute.",
+            " */",
+        ];
+        assert_eq!(
+            likeness(&license(&proprietary, Some(0), Some(4)), &template(), 0.5),
+            Likeness::Similar
+        );
+        assert_eq!(
+            likeness(&license(&proprietary, Some(0), Some(4)), &template(), 0.95),
+            Likeness::Different
+        );
+        let older = ["/*", " * Copyright (c) 2023 Acme", " * Licensed under MIT.", " */"];
+        assert_eq!(
+            likeness(&license(&older, Some(0), Some(3)), &template(), 0.0),
+            Likeness::Different
+        );
+    }
 }
@@ -3,6 +3,10 @@ use std::path::{Path, PathBuf};
 use walkdir::{DirEntry, WalkDir};
 
 fn is_excluded(entry: &DirEntry, excludes: &[OsString]) -> bool {
+    if entry.file_name() == ".git" {
+        log::debug!("{:?} belongs to git, skipping.", entry.path());
+        return true;
+    }
     for exclude in excludes {
         if exclude == entry.file_name() {
             log::info!("Directory {:?} is excluded, skipping.", entry.path());
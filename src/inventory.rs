@@ -0,0 +1,397 @@
+use crate::copywriter::Header;
+use crate::spdx::{guess_license, license_terms, validate_expression};
+use crate::{SPDX_ID_PATTERN, YEARS_PATTERN};
+use chrono::SecondsFormat;
+use lazy_static::lazy_static;
+use regex::{Regex, RegexBuilder};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{read, read_to_string};
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    // a year or a copyright sign has to follow, license texts also have lines starting with "copyright notice"
+    static ref COPYRIGHT_PATTERN: Regex =
+        RegexBuilder::new(r"^(?:SPDX-FileCopyrightText:|Copyright\s*(?:©|\(c\)|[0-9]{4})|©|\(c\)\s*[0-9]{4})")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+    static ref COPYRIGHT_PREFIX_PATTERN: Regex =
+        RegexBuilder::new(r"^(?:SPDX-FileCopyrightText:|Copyright\b|©|\(c\))\s*(?:(?:Copyright\b|©|\(c\))\s*)*")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+    static ref RESERVED_PATTERN: Regex = RegexBuilder::new(r"[\s,;]*All rights reserved\.?$")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+}
+
+// company name abbreviations keeping their period
+const ABBREVIATIONS: [&str; 6] = ["Inc", "Ltd", "Corp", "Co", "Bros", "Intl"];
+
+// comment closers that may end a header line
+const CLOSERS: [&str; 9] = ["*/", "-->", "--%>", "--}}", "%>", "*)", "#>", "-}", "*@"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Spdx,
+    SpdxJson,
+    Csv,
+}
+
+// copyright and license information of a file or directory
+#[derive(Debug, Default)]
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+    pub language: String,
+    pub copyrights: Vec<String>,
+    pub holders: BTreeSet<String>,
+    pub years: BTreeSet<String>,
+    pub licenses: BTreeSet<String>,
+}
+
+// the header line without comment decoration
+pub fn strip_decoration(line: &str) -> &str {
+    let mut text = line.trim();
+    text = text.trim_start_matches(|c: char| !c.is_alphanumeric() && c != '(' && c != '©');
+    for closer in CLOSERS {
+        text = text.strip_suffix(closer).unwrap_or(text).trim_end();
+    }
+    text
+}
+
+// copyright statement of the header line, if it is one
+pub fn copyright(line: &str) -> Option<&str> {
+    let text = strip_decoration(line);
+    if COPYRIGHT_PATTERN.is_match(text) {
+        return Some(text);
+    }
+    None
+}
+
+fn holder(copyright: &str) -> Option<String> {
+    let text = COPYRIGHT_PREFIX_PATTERN.replace(copyright, "");
+    let text = text.trim_start_matches(|c: char| c.is_ascii_digit() || c.is_whitespace() || c == '-' || c == ',');
+    let text = RESERVED_PATTERN.replace(text, "");
+    let mut text = text.trim();
+    // the period ending a sentence isn't part of the name, the one of "Inc." or "B.V." is
+    if let Some(stripped) = text.strip_suffix('.') {
+        let word = stripped.rsplit(char::is_whitespace).next().unwrap_or_default();
+        if !word.contains('.') && !ABBREVIATIONS.contains(&word) {
+            text = stripped;
+        }
+    }
+    if text.is_empty() {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+pub fn entry(root: &Path, header: &Header) -> Entry {
+    let relative = header.path.strip_prefix(root).unwrap_or(&header.path);
+    let mut entry = Entry {
+        name: format!("./{}", relative.to_string_lossy().replace('\\', "/")),
+        path: header.path.clone(),
+        language: header.language.clone(),
+        ..Default::default()
+    };
+    for line in &header.lines {
+        if let Some(copyright) = copyright(line) {
+            entry.copyrights.push(copyright.to_string());
+            entry.holders.extend(holder(copyright));
+            entry
+                .years
+                .extend(YEARS_PATTERN.captures_iter(copyright).map(|c| c[2].to_string()));
+        }
+        if let Some(captures) = SPDX_ID_PATTERN.captures(line) {
+            entry.licenses.insert(captures[1].to_string());
+        }
+    }
+    if entry.licenses.is_empty() {
+        let text: Vec<&str> = header.lines.iter().map(|line| strip_decoration(line)).collect();
+        entry.licenses.extend(guess_license(&text.join(" ")).map(String::from));
+    }
+    entry
+}
+
+// summary of every directory containing scanned files, the root being "."
+// only exported as CSV, SPDX 2.3 has no element for directories
+pub fn directories(entries: &[Entry]) -> BTreeMap<String, Entry> {
+    let mut directories: BTreeMap<String, Entry> = BTreeMap::new();
+    for entry in entries {
+        for ancestor in Path::new(&entry.name).ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                continue;
+            }
+            let name = ancestor.to_string_lossy().to_string();
+            let directory = directories.entry(name.clone()).or_insert_with(|| Entry {
+                name,
+                ..Default::default()
+            });
+            directory.holders.extend(entry.holders.iter().cloned());
+            directory.years.extend(entry.years.iter().cloned());
+            directory.licenses.extend(entry.licenses.iter().cloned());
+        }
+    }
+    directories
+}
+
+fn join(values: &BTreeSet<String>) -> String {
+    values.iter().cloned().collect::<Vec<String>>().join("; ")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    value.to_string()
+}
+
+pub fn to_csv(entries: &[Entry]) -> String {
+    let mut csv = String::from("type,path,language,copyrights,holders,years,licenses\n");
+    let directories = directories(entries);
+    let rows = entries
+        .iter()
+        .map(|e| ("file", e))
+        .chain(directories.values().map(|e| ("directory", e)));
+    for (kind, entry) in rows {
+        let fields = [
+            kind.to_string(),
+            entry.name.clone(),
+            entry.language.clone(),
+            entry.copyrights.join("; "),
+            join(&entry.holders),
+            join(&entry.years),
+            join(&entry.licenses),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn checksum(path: &Path) -> String {
+    match read(path) {
+        Ok(buffer) => openssl::sha::sha1(&buffer)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+        Err(why) => {
+            log::error!("Can't read {:?} for checksum, {}", path, why);
+            String::new()
+        }
+    }
+}
+
+fn license_info(entry: &Entry) -> Vec<String> {
+    if entry.licenses.is_empty() {
+        return vec![String::from("NONE")];
+    }
+    let terms: BTreeSet<String> = entry
+        .licenses
+        .iter()
+        .filter(|l| validate_expression(l).is_ok())
+        .flat_map(|l| license_terms(l))
+        .collect();
+    if terms.is_empty() {
+        return vec![String::from("NOASSERTION")];
+    }
+    terms.into_iter().collect()
+}
+
+// LicenseRef-* identifiers have to be declared with their text, taken from LICENSES/<id>.txt as REUSE lays it out
+fn extracted_licenses(root: &Path, entries: &[Entry]) -> Vec<(String, String)> {
+    let ids: BTreeSet<String> = entries
+        .iter()
+        .flat_map(license_info)
+        .filter_map(|term| term.split(' ').next().map(String::from))
+        .filter(|id| id.starts_with("LicenseRef-"))
+        .collect();
+    ids.into_iter()
+        .map(|id| {
+            let text = read_to_string(root.join("LICENSES").join(format!("{}.txt", id)))
+                .unwrap_or_else(|_| format!("{} is referenced in the file headers, its text was not found.", id));
+            (id, text)
+        })
+        .collect()
+}
+
+fn copyright_text(entry: &Entry) -> String {
+    if entry.copyrights.is_empty() {
+        return String::from("NONE");
+    }
+    entry.copyrights.join("\n")
+}
+
+struct Document {
+    name: String,
+    namespace: String,
+    created: String,
+}
+
+fn document(root: &Path) -> Document {
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| String::from("root"));
+    let created = chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let unique: String = openssl::sha::sha1(format!("{:?}{}", root, created).as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Document {
+        namespace: format!("https://spdx.org/spdxdocs/{}-{}", name, unique),
+        name,
+        created,
+    }
+}
+
+pub fn to_spdx(root: &Path, entries: &[Entry], creator: &str) -> String {
+    let document = document(root);
+    let mut spdx = String::new();
+    spdx.push_str("SPDXVersion: SPDX-2.3\n");
+    spdx.push_str("DataLicense: CC0-1.0\n");
+    spdx.push_str("SPDXID: SPDXRef-DOCUMENT\n");
+    spdx.push_str(&format!("DocumentName: {}\n", document.name));
+    spdx.push_str(&format!("DocumentNamespace: {}\n", document.namespace));
+    spdx.push_str(&format!("Creator: Tool: {}\n", creator));
+    spdx.push_str(&format!("Created: {}\n", document.created));
+    for (i, entry) in entries.iter().enumerate() {
+        spdx.push('\n');
+        spdx.push_str(&format!("FileName: {}\n", entry.name));
+        spdx.push_str(&format!("SPDXID: SPDXRef-File-{}\n", i + 1));
+        spdx.push_str(&format!("FileChecksum: SHA1: {}\n", checksum(&entry.path)));
+        spdx.push_str("LicenseConcluded: NOASSERTION\n");
+        for id in license_info(entry) {
+            spdx.push_str(&format!("LicenseInfoInFile: {}\n", id));
+        }
+        if entry.copyrights.is_empty() {
+            spdx.push_str("FileCopyrightText: NONE\n");
+        } else {
+            spdx.push_str(&format!("FileCopyrightText: <text>{}</text>\n", copyright_text(entry)));
+        }
+    }
+    for (id, text) in extracted_licenses(root, entries) {
+        spdx.push('\n');
+        spdx.push_str(&format!("LicenseID: {}\n", id));
+        spdx.push_str(&format!("ExtractedText: <text>{}</text>\n", text.trim_end()));
+    }
+    spdx
+}
+
+pub fn to_spdx_json(root: &Path, entries: &[Entry], creator: &str) -> String {
+    let document = document(root);
+    let files: Vec<serde_json::Value> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            json!({
+                "fileName": entry.name,
+                "SPDXID": format!("SPDXRef-File-{}", i + 1),
+                "checksums": [{"algorithm": "SHA1", "checksumValue": checksum(&entry.path)}],
+                "licenseConcluded": "NOASSERTION",
+                "licenseInfoInFiles": license_info(entry),
+                "copyrightText": copyright_text(entry),
+            })
+        })
+        .collect();
+    let extracted: Vec<serde_json::Value> = extracted_licenses(root, entries)
+        .into_iter()
+        .map(|(id, text)| json!({"licenseId": id, "extractedText": text.trim_end()}))
+        .collect();
+    let mut spdx = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": document.name,
+        "documentNamespace": document.namespace,
+        "creationInfo": {
+            "creators": [format!("Tool: {}", creator)],
+            "created": document.created,
+        },
+        "files": files,
+    });
+    if !extracted.is_empty() {
+        spdx["hasExtractedLicensingInfos"] = json!(extracted);
+    }
+    serde_json::to_string_pretty(&spdx).unwrap()
+}
+
+pub fn export(root: &Path, headers: &[Header], format: Format, creator: &str) -> String {
+    let mut entries: Vec<Entry> = headers.iter().map(|h| entry(root, h)).collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    match format {
+        Format::Spdx => to_spdx(root, &entries, creator),
+        Format::SpdxJson => to_spdx_json(root, &entries, creator),
+        Format::Csv => to_csv(&entries),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn license_text_is_not_a_copyright() {
+        let bsd = [
+            " * Redistributions of source code must retain the above",
+            " * copyright notice, this list of conditions and the following disclaimer.",
+            " * Copyright holders and contributors may not be used to endorse or promote products",
+        ];
+        for line in bsd {
+            assert_eq!(copyright(line), None, "{}", line);
+        }
+        assert_eq!(copyright(" * (c) You must cause any modified files to carry notices"), None);
+        for line in [
+            " * Copyright (c) 2015 The Go Authors",
+            "# Copyright 2015-2024 The Go Authors",
+            "// © Acme",
+            "SPDX-FileCopyrightText: Acme",
+        ] {
+            assert!(copyright(line).is_some(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn holder_keeps_abbreviations() {
+        assert_eq!(
+            holder("Copyright (c) 2024 Foo Inc. All rights reserved."),
+            Some(String::from("Foo Inc."))
+        );
+        assert_eq!(
+            holder("Copyright (c) 2024 Acme, All rights reserved"),
+            Some(String::from("Acme"))
+        );
+        assert_eq!(
+            holder("Copyright (c) 2009 The Go Authors. All rights reserved."),
+            Some(String::from("The Go Authors"))
+        );
+        assert_eq!(holder("Copyright 2024 Acme B.V."), Some(String::from("Acme B.V.")));
+    }
+
+    #[test]
+    fn license_info_keeps_exceptions_and_later_versions() {
+        let entry = Entry {
+            licenses: BTreeSet::from([
+                String::from("(GPL-2.0+ OR MIT) AND GPL-2.0-or-later WITH Classpath-exception-2.0"),
+                String::from("LicenseRef-Acme"),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            license_info(&entry),
+            vec![
+                "GPL-2.0+",
+                "GPL-2.0-or-later WITH Classpath-exception-2.0",
+                "LicenseRef-Acme",
+                "MIT"
+            ]
+        );
+        let extracted = extracted_licenses(Path::new("/nonexistent"), &[entry]);
+        assert_eq!(extracted.len(), 1);
+        assert_eq!(extracted[0].0, "LicenseRef-Acme");
+    }
+}
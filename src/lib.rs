@@ -4,6 +4,10 @@ use regex::{Regex, RegexBuilder};
 pub mod copywriter;
 pub mod filesystem;
 pub mod git;
+pub mod inventory;
+pub mod notice;
+pub mod reuse;
+pub mod spdx;
 pub mod template;
 
 lazy_static! {
@@ -13,5 +17,9 @@ lazy_static! {
             .build()
             .unwrap();
     static ref LICENSE_PATTERN: Regex = RegexBuilder::new(r"license").case_insensitive(true).build().unwrap();
+    static ref SPDX_PATTERN: Regex = Regex::new(r"SPDX-(?:License-Identifier|FileCopyrightText)\s*:").unwrap();
+    static ref SPDX_ID_PATTERN: Regex =
+        Regex::new(r"SPDX-License-Identifier:\s*(.*?)\s*(?:\*/|-->|--%>|--\}\}|%>|\*@|#>|-\}|\*\)|$)").unwrap();
+    static ref COPYRIGHT_SIGN_PATTERN: Regex = Regex::new(r"\(\s*c\s*\)|©").unwrap();
     static ref EMPTY_PATTERN: Regex = RegexBuilder::new(r"^\s*$").build().unwrap();
 }
@@ -1,16 +1,310 @@
-use clap::{Arg, ArgAction, Command};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use regex::Regex;
 use std::ffi::OsString;
-use std::fs::canonicalize;
-use std::path::PathBuf;
+use std::fs::{canonicalize, read_to_string, write};
+use std::path::{Path, PathBuf};
 use std::process::exit;
 
-use copywrite::copywriter;
+use copywrite::copywriter::{self, Options, ThirdParty};
 use copywrite::filesystem;
 use copywrite::git;
-use copywrite::template::read_template;
+use copywrite::inventory::{export, Format};
+use copywrite::notice::{render, third_party_notices};
+use copywrite::reuse;
+use copywrite::spdx::validate_expression;
+use copywrite::template::{bundled_licenses, bundled_template, read_template};
 
 const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), ".", env!("BUILD"));
 
+fn language_arg() -> Arg {
+    Arg::new("LANGUAGE")
+        .short('l')
+        .long("language")
+        .action(ArgAction::Append)
+        .help("Restrict to only update files for specified language(s), can be repeated.")
+}
+
+fn map_arg() -> Arg {
+    Arg::new("MAP")
+        .short('m')
+        .long("map")
+        .action(ArgAction::Append)
+        .value_name("SUFFIX=LANGUAGE")
+        .help("Map a file suffix (e.g. .h or .d.ts) or file name (e.g. Jenkinsfile) to a language, overriding the built-in detection, can be repeated. Use the language \"ignore\" to skip matching files.")
+}
+
+fn exclude_arg() -> Arg {
+    Arg::new("EXCLUDE")
+        .short('e')
+        .long("exclude")
+        .action(ArgAction::Append)
+        .help("Exclude path, file or directory name, can be repeated.")
+}
+
+fn gitindex_arg() -> Arg {
+    Arg::new("GITINDEX")
+        .short('g')
+        .long("gitindex")
+        .action(ArgAction::SetTrue)
+        .help("Filter on files in git index only.")
+}
+
+fn template_arg() -> Arg {
+    Arg::new("TEMPLATE")
+        .short('t')
+        .long("template")
+        .num_args(1)
+        .help("Path to tera (Jinja2) template file containing the copyright banner. All environment variables plus {{year}} for current year are available in the template.")
+}
+
+fn license_arg() -> Arg {
+    Arg::new("LICENSE")
+        .long("license")
+        .num_args(1)
+        .value_parser(PossibleValuesParser::new(bundled_licenses()))
+        .requires("HOLDER")
+        .conflicts_with("TEMPLATE")
+        .help("Use the bundled header template for the SPDX license identifier instead of a template file.")
+}
+
+fn holder_arg() -> Arg {
+    Arg::new("HOLDER")
+        .long("holder")
+        .num_args(1)
+        .help("Copyright holder, available as {{holder}} in the template.")
+}
+
+fn spdx_arg() -> Arg {
+    Arg::new("SPDX")
+        .short('s')
+        .long("spdx")
+        .num_args(1)
+        .value_name("EXPRESSION")
+        .help("SPDX license expression, e.g. \"Apache-2.0 OR MIT\", available as {{spdx_id}} in the template.")
+}
+
+fn owner_arg() -> Arg {
+    Arg::new("OWNER")
+        .short('o')
+        .long("owner")
+        .num_args(1)
+        .value_name("REGEX")
+        .help("Regular expression matching our own copyright, existing headers not matching it are considered third-party.")
+}
+
+fn third_party_arg() -> Arg {
+    Arg::new("THIRDPARTY")
+        .long("third-party")
+        .num_args(1)
+        .value_parser(["skip", "above", "append"])
+        .default_value("skip")
+        .requires("OWNER")
+        .help("What to do with third-party headers, skip the file, add our header above the existing one or append our copyright line to the existing notice.")
+}
+
+fn similarity_arg() -> Arg {
+    Arg::new("SIMILARITY")
+        .long("similarity")
+        .num_args(1)
+        .value_name("RATIO")
+        .value_parser(clap::value_parser!(f64))
+        .help("Keep existing headers with the same years whose words match the template at least by this ratio (0.0-1.0), ignoring comment decoration, whitespace, case and (c)/©. Files only differing cosmetically and files worded differently are reported separately.")
+}
+
+fn get_path(matches: &ArgMatches) -> PathBuf {
+    let path = match canonicalize(PathBuf::from(matches.get_one::<String>("PATH").unwrap())) {
+        Ok(p) => p,
+        Err(why) => {
+            println!("Can't canonicalize path, {}", why);
+            exit(1);
+        }
+    };
+    if !path.exists() {
+        log::error!("Invalid path {:?}", path);
+        exit(2);
+    }
+    path
+}
+
+fn get_excludes(matches: &ArgMatches) -> Vec<OsString> {
+    match matches.get_many::<String>("EXCLUDE") {
+        Some(l) => l.map(OsString::from).collect(),
+        None => vec![],
+    }
+}
+
+fn get_languages(matches: &ArgMatches) -> Option<Vec<&str>> {
+    matches
+        .get_many::<String>("LANGUAGE")
+        .map(|l| l.map(|s| s.as_str()).collect())
+}
+
+fn get_mappings(matches: &ArgMatches) -> Vec<(String, String)> {
+    let mut mappings: Vec<(String, String)> = Vec::new();
+    if let Some(m) = matches.get_many::<String>("MAP") {
+        for mapping in m {
+            match mapping.split_once('=') {
+                Some((suffix, language)) if !suffix.is_empty() && !language.is_empty() => {
+                    mappings.push((suffix.to_string(), language.to_string()))
+                }
+                _ => {
+                    log::error!("Invalid mapping {:?}, expected SUFFIX=LANGUAGE", mapping);
+                    exit(6);
+                }
+            }
+        }
+    }
+    mappings
+}
+
+fn get_template(matches: &ArgMatches) -> Vec<String> {
+    let mut variables: Vec<(&str, &str)> = Vec::new();
+    if let Some(spdx_id) = matches.get_one::<String>("SPDX") {
+        if let Err(why) = validate_expression(spdx_id) {
+            log::error!("Invalid SPDX license expression {:?}, {}", spdx_id, why);
+            exit(8);
+        }
+        variables.push(("spdx_id", spdx_id));
+    }
+    if let Some(holder) = matches.get_one::<String>("HOLDER") {
+        variables.push(("holder", holder));
+    }
+    match matches.get_one::<String>("LICENSE") {
+        Some(license) => {
+            if !matches.contains_id("SPDX") {
+                variables.push(("spdx_id", license));
+            }
+            let reuse = matches!(matches.try_get_one::<bool>("REUSE"), Ok(Some(true)));
+            bundled_template(license, &variables, reuse)
+        }
+        None => read_template(&PathBuf::from(matches.get_one::<String>("TEMPLATE").unwrap()), &variables),
+    }
+}
+
+fn get_options(matches: &ArgMatches) -> Options {
+    let mut options = Options {
+        mappings: get_mappings(matches),
+        ..Default::default()
+    };
+    if let Some(owner) = matches.get_one::<String>("OWNER") {
+        options.owner = match Regex::new(owner) {
+            Ok(r) => Some(r),
+            Err(why) => {
+                log::error!("Invalid owner pattern, {}", why);
+                exit(7);
+            }
+        };
+    }
+    options.third_party = match matches.get_one::<String>("THIRDPARTY").map(|s| s.as_str()) {
+        Some("above") => ThirdParty::Above,
+        Some("append") => ThirdParty::Append,
+        _ => ThirdParty::Skip,
+    };
+    if let Some(&similarity) = matches.get_one::<f64>("SIMILARITY") {
+        if !(0.0..=1.0).contains(&similarity) {
+            log::error!("Invalid similarity {}, must be between 0.0 and 1.0", similarity);
+            exit(16);
+        }
+        options.similarity = Some(similarity);
+    }
+    options
+}
+
+fn get_index_files(path: &Path, excludes: &[OsString]) -> Vec<OsString> {
+    match git::git_index(path, excludes) {
+        Ok(f) => f,
+        Err(why) => {
+            log::error!("{}", why);
+            exit(3);
+        }
+    }
+}
+
+fn inventory(matches: &ArgMatches) {
+    let path = get_path(matches);
+    let excludes = get_excludes(matches);
+    let files = if matches.get_flag("GITINDEX") {
+        get_index_files(&path, &excludes)
+    } else {
+        filesystem::walk(&path, &excludes)
+    };
+    let headers = copywriter::scan_path(&files, &get_languages(matches), &get_mappings(matches));
+    let format = match matches.get_one::<String>("FORMAT").map(|s| s.as_str()) {
+        Some("spdx-json") => Format::SpdxJson,
+        Some("csv") => Format::Csv,
+        _ => Format::Spdx,
+    };
+    let creator = format!("copywrite-{}", VERSION);
+    let document = export(&path, &headers, format, &creator);
+    match matches.get_one::<String>("OUTPUT") {
+        Some(output) => {
+            if let Err(why) = write(output, document) {
+                log::error!("Can't write inventory to {:?}, {}", output, why);
+                exit(14);
+            }
+        }
+        None => print!("{}", document),
+    }
+}
+
+fn notice(matches: &ArgMatches) {
+    let path = get_path(matches);
+    let excludes = get_excludes(matches);
+    let owner = match Regex::new(matches.get_one::<String>("OWNER").unwrap()) {
+        Ok(r) => r,
+        Err(why) => {
+            log::error!("Invalid owner pattern, {}", why);
+            exit(7);
+        }
+    };
+    let files = if matches.get_flag("GITINDEX") {
+        get_index_files(&path, &excludes)
+    } else {
+        filesystem::walk(&path, &excludes)
+    };
+    let headers = copywriter::scan_path(&files, &get_languages(matches), &get_mappings(matches));
+    let notices = third_party_notices(&headers, &owner);
+    let text = render(matches.get_one::<String>("TITLE").unwrap(), &notices);
+    let output = match matches.get_one::<String>("OUTPUT") {
+        Some(o) => PathBuf::from(o),
+        None => path.join("NOTICE"),
+    };
+    let existing = read_to_string(&output).unwrap_or_default();
+    if existing == text {
+        log::info!("{:?} is up-to-date", output);
+    } else if matches.get_flag("CHECK") {
+        log::error!("{:?} is out of date, {} third-party notice(s) found", output, notices.len());
+        exit(15);
+    } else {
+        log::info!("Writing {:?}", output);
+        if let Err(why) = write(&output, text) {
+            log::error!("Can't write {:?}, {}", output, why);
+            exit(14);
+        }
+    }
+}
+
+fn verify(matches: &ArgMatches) {
+    let path = get_path(matches);
+    let excludes = get_excludes(matches);
+    let template = get_template(matches);
+    let options = get_options(matches);
+    let files = if matches.get_flag("GITINDEX") {
+        get_index_files(&path, &excludes)
+    } else {
+        filesystem::walk(&path, &excludes)
+    };
+    let failures = copywriter::verify_path(&files, &template, &get_languages(matches), &options);
+    for failure in &failures {
+        log::error!("{:?}: {}", failure.path, failure.reason);
+    }
+    if !failures.is_empty() {
+        log::error!("{} file(s) failed verification", failures.len());
+        exit(17);
+    }
+}
+
 fn main() {
     env_logger::builder().format_timestamp(None).init();
 
@@ -20,41 +314,104 @@ fn main() {
         .arg(Arg::new("BUILD")
                 .short('v')
                 .action(ArgAction::SetTrue)
-                .conflicts_with_all(["PATH", "TEMPLATE"])
+                .conflicts_with_all(["PATH", "TEMPLATE", "LICENSE"])
                 .help("Prints shorthand version information."))
         .arg(Arg::new("PATH")
              .required(true)
              .index(1)
              .conflicts_with("BUILD"))
-        .arg(Arg::new("TEMPLATE")
-             .short('t')
-             .long("template")
-             .required(true)
-             .num_args(1)
-             .help("Path to tera (Jinja2) template file containing the copyright banner. All environment variables plus {{year}} for current year are available in the template."))
-        .arg(Arg::new("LANGUAGE")
-             .short('l')
-             .long("language")
-             .action(ArgAction::Append)
-             .help("Restrict to only update files for specified language(s), can be repeated."))
-        .arg(Arg::new("EXCLUDE")
-             .short('e')
-             .long("exclude")
-             .action(ArgAction::Append)
-             .help("Exclude path, file or directory name, can be repeated."))
-        .arg(Arg::new("GITINDEX")
-             .short('g')
-             .long("gitindex")
+        .arg(template_arg().required_unless_present_any(["BUILD", "LICENSE"]))
+        .arg(license_arg())
+        .arg(holder_arg())
+        .arg(spdx_arg())
+        .arg(Arg::new("REUSE")
+             .short('r')
+             .long("reuse")
              .action(ArgAction::SetTrue)
-             .conflicts_with("GITSTAGED")
-             .help("Filter on files in git index only."))
+             .help("REUSE compliance mode, writes <file>.license files for files that can't hold a header and reports files and licenses missing from LICENSES/."))
+        .arg(language_arg())
+        .arg(map_arg())
+        .arg(owner_arg())
+        .arg(third_party_arg())
+        .arg(similarity_arg())
+        .arg(exclude_arg())
+        .arg(gitindex_arg().conflicts_with("GITSTAGED"))
         .arg(Arg::new("GITSTAGED")
              .short('d')
              .long("gitstaged")
              .action(ArgAction::SetTrue)
              .conflicts_with_all(["GITINDEX", "EXCLUDE"])
              .help("Filter on files added to git staging index only."))
-        .after_help("Supported languages: c, cpp, csharp, rust, go, swift, objective-c, kotlin, java, javascript, groovy, php, typescript, python, xml, svg, resx, proto, html, css, script")
+        .after_help("Supported languages: c, cpp, csharp, rust, go, swift, objective-c, kotlin, java, javascript, groovy, php, typescript, python, xml, svg, resx, proto, html, css, script, blade, erb, perl, ruby, r, yaml, toml, cmake, makefile, dockerfile, terraform, nix, powershell, sql, lua, haskell, elisp, clojure, scheme, racket, asm, ini, ocaml, fsharp, sml, pascal, mathematica, erlang, elixir, matlab, latex, prolog, verilog, vhdl, batch, vue, svelte, astro, razor, jsp, markdown, rst")
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(Command::new("verify")
+            .about("Check that updating the headers is idempotent and only touches the header, without changing any file.")
+            .arg(Arg::new("PATH")
+                 .required(true)
+                 .index(1))
+            .arg(template_arg().required_unless_present("LICENSE"))
+            .arg(license_arg())
+            .arg(holder_arg())
+            .arg(spdx_arg())
+            .arg(language_arg())
+            .arg(map_arg())
+            .arg(owner_arg())
+            .arg(third_party_arg())
+            .arg(similarity_arg())
+            .arg(exclude_arg())
+            .arg(gitindex_arg()))
+        .subcommand(Command::new("inventory")
+            .about("Export the copyright holders, years and licenses found in the headers of all files.")
+            .arg(Arg::new("PATH")
+                 .required(true)
+                 .index(1))
+            .arg(Arg::new("FORMAT")
+                 .short('f')
+                 .long("format")
+                 .num_args(1)
+                 .value_parser(["spdx", "spdx-json", "csv"])
+                 .default_value("spdx")
+                 .help("Export as SPDX tag-value, SPDX JSON or CSV with a summary per directory."))
+            .arg(Arg::new("OUTPUT")
+                 .short('o')
+                 .long("output")
+                 .num_args(1)
+                 .help("File to write the inventory to, standard output if not given."))
+            .arg(language_arg())
+            .arg(map_arg())
+            .arg(exclude_arg())
+            .arg(gitindex_arg()))
+        .subcommand(Command::new("notice")
+            .about("Generate or verify a NOTICE file listing the third-party copyrights found in the headers of all files.")
+            .arg(Arg::new("PATH")
+                 .required(true)
+                 .index(1))
+            .arg(Arg::new("OWNER")
+                 .short('o')
+                 .long("owner")
+                 .required(true)
+                 .num_args(1)
+                 .value_name("REGEX")
+                 .help("Regular expression matching our own copyright, all other copyright lines are listed."))
+            .arg(Arg::new("OUTPUT")
+                 .long("output")
+                 .num_args(1)
+                 .help("NOTICE file to write or verify, NOTICE in PATH if not given."))
+            .arg(Arg::new("TITLE")
+                 .long("title")
+                 .num_args(1)
+                 .default_value("NOTICE")
+                 .help("First line of the NOTICE file."))
+            .arg(Arg::new("CHECK")
+                 .short('c')
+                 .long("check")
+                 .action(ArgAction::SetTrue)
+                 .help("Don't write the file, fail if it is out of date."))
+            .arg(language_arg())
+            .arg(map_arg())
+            .arg(exclude_arg())
+            .arg(gitindex_arg()))
         .get_matches();
 
     // version
@@ -63,45 +420,49 @@ fn main() {
         exit(0);
     }
 
-    // validate path
-    let path = match canonicalize(PathBuf::from(matches.get_one::<String>("PATH").unwrap())) {
-        Ok(p) => p,
-        Err(why) => {
-            println!("Can't canonicalize path, {}", why);
-            exit(1);
+    match matches.subcommand() {
+        Some(("verify", sub_matches)) => {
+            verify(sub_matches);
+            exit(0);
         }
-    };
-    if !path.exists() {
-        log::error!("Invalid path {:?}", path);
-        exit(2);
+        Some(("inventory", sub_matches)) => {
+            inventory(sub_matches);
+            exit(0);
+        }
+        Some(("notice", sub_matches)) => {
+            notice(sub_matches);
+            exit(0);
+        }
+        _ => {}
     }
 
+    // validate path
+    let path = get_path(&matches);
+
     // template
-    let template_path = PathBuf::from(matches.get_one::<String>("TEMPLATE").unwrap());
-    let template: Vec<String> = read_template(&template_path);
+    let template = get_template(&matches);
+    if matches.get_flag("REUSE") {
+        if let Err(why) = reuse::check_template(&template) {
+            log::error!("Template can't be used in REUSE mode, {}", why);
+            exit(9);
+        }
+    }
 
     // exclude
-    let excludes: Vec<OsString> = match matches.get_many::<String>("EXCLUDE") {
-        Some(l) => l.map(OsString::from).collect(),
-        None => vec![],
-    };
+    let excludes = get_excludes(&matches);
 
     // languages
-    let languages: Option<Vec<&str>> = matches
-        .get_many::<String>("LANGUAGE")
-        .map(|l| l.map(|s| s.as_str()).collect());
+    let languages = get_languages(&matches);
+
+    // mappings, owner and third-party handling
+    let mut options = get_options(&matches);
+    options.reuse = matches.get_flag("REUSE");
 
     // git index / staged
     let files = if matches.get_flag("GITINDEX") {
-        match git::git_index(&path, &excludes) {
-            Ok(f) => f,
-            Err(why) => {
-                log::error!("{}", why);
-                exit(3);
-            }
-        }
+        get_index_files(&path, &excludes)
     } else if matches.get_flag("GITSTAGED") {
-        match git::git_staged(&path) {
+        match git::git_staged(&path, &excludes) {
             Ok(f) => f,
             Err(why) => {
                 log::error!("{}", why);
@@ -113,7 +474,29 @@ fn main() {
     };
 
     // update all files
-    copywriter::copywrite_path(&files, &template, &languages);
+    let report = copywriter::copywrite_path(&files, &template, &languages, &options);
+    if !report.cosmetic.is_empty() {
+        println!("Headers only differing cosmetically from the template:");
+        for file in &report.cosmetic {
+            println!("  {}", file.display());
+        }
+    }
+    if !report.similar.is_empty() {
+        println!("Headers kept as similar to the template, but worded differently:");
+        for file in &report.similar {
+            println!("  {}", file.display());
+        }
+    }
+
+    // new license files have to be in the index too, or the staged tree isn't compliant
+    if options.reuse && (matches.get_flag("GITINDEX") || matches.get_flag("GITSTAGED")) && !report.sidecars.is_empty() {
+        let sidecars: Vec<OsString> = report.sidecars.iter().map(|p| p.as_os_str().to_os_string()).collect();
+        log::info!("Adding {:?}", sidecars);
+        if let Err(why) = git::git_add(&path, &sidecars) {
+            log::error!("{}", why);
+            exit(5);
+        }
+    }
 
     // if using gitstaged re-add updated files
     if matches.get_flag("GITSTAGED") {
@@ -128,6 +511,12 @@ fn main() {
         }
     }
 
+    // check the result against the REUSE specification
+    if options.reuse && !reuse::lint(&path, &report.checked) {
+        log::error!("Not compliant with the REUSE specification.");
+        exit(13);
+    }
+
     log::debug!("Done!");
     exit(0);
 }
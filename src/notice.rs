@@ -0,0 +1,93 @@
+use crate::copywriter::Header;
+use crate::inventory::copyright;
+use regex::Regex;
+use std::collections::BTreeSet;
+
+// distinct copyright statements of everyone not matching the owner pattern
+pub fn third_party_notices(headers: &[Header], owner: &Regex) -> BTreeSet<String> {
+    let mut notices: BTreeSet<String> = BTreeSet::new();
+    for header in headers {
+        for line in &header.lines {
+            if let Some(text) = copyright(line) {
+                if owner.is_match(text) {
+                    continue;
+                }
+                let notice = match text.strip_prefix("SPDX-FileCopyrightText:") {
+                    Some(rest) if rest.trim_start().to_lowercase().starts_with("copyright") => rest.trim().to_string(),
+                    Some(rest) => format!("Copyright {}", rest.trim()),
+                    None => text.to_string(),
+                };
+                log::debug!("Found third-party notice {:?} in {:?}", notice, header.path);
+                notices.insert(notice);
+            }
+        }
+    }
+    notices
+}
+
+pub fn render(title: &str, notices: &BTreeSet<String>) -> String {
+    let mut text = format!("{}\n", title);
+    if !notices.is_empty() {
+        text.push_str("\nThis product includes software with the following copyright notices:\n\n");
+        for notice in notices {
+            text.push_str(notice);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn header(path: &str, lines: &[&str]) -> Header {
+        Header {
+            path: PathBuf::from(path),
+            language: String::from("c"),
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn notices_skip_the_owner_and_are_sorted_without_duplicates() {
+        let headers = [
+            header("a.c", &["/*", " * Copyright (c) 2024 Acme Corp.", " */"]),
+            header(
+                "b.c",
+                &[
+                    "// Copyright (c) 2015 Google LLC",
+                    "// SPDX-License-Identifier: Apache-2.0",
+                ],
+            ),
+            header(
+                "c.c",
+                &[
+                    "# SPDX-FileCopyrightText: 2019 Beta Ltd",
+                    "# Copyright (c) 2015 Google LLC",
+                ],
+            ),
+            header("d.c", &["-- SPDX-FileCopyrightText: Copyright 2020 Acme Corp."]),
+        ];
+        let notices = third_party_notices(&headers, &Regex::new("Acme").unwrap());
+        assert_eq!(
+            notices.into_iter().collect::<Vec<String>>(),
+            vec!["Copyright (c) 2015 Google LLC", "Copyright 2019 Beta Ltd"]
+        );
+    }
+
+    #[test]
+    fn render_lists_notices_below_the_title() {
+        assert_eq!(render("NOTICE", &BTreeSet::new()), "NOTICE\n");
+        let notices: BTreeSet<String> = ["Copyright 2019 Beta Ltd", "Copyright (c) 2015 Google LLC"]
+            .iter()
+            .map(|notice| notice.to_string())
+            .collect();
+        assert_eq!(
+            render("Acme Widgets", &notices),
+            "Acme Widgets\n\nThis product includes software with the following copyright notices:\n\n\
+             Copyright (c) 2015 Google LLC\nCopyright 2019 Beta Ltd\n"
+        );
+    }
+}
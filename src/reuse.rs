@@ -0,0 +1,139 @@
+use crate::spdx::{license_ids, validate_expression};
+use crate::SPDX_ID_PATTERN;
+use std::collections::BTreeSet;
+use std::ffi::OsStr;
+use std::fs::{read, write};
+use std::path::{Path, PathBuf};
+
+// support for the REUSE specification, see https://reuse.software/spec/
+
+const COPYRIGHT_TAG: &str = "SPDX-FileCopyrightText:";
+const LICENSE_TAG: &str = "SPDX-License-Identifier:";
+
+// files the specification excludes from the copyright and licensing requirements
+pub fn is_ignored(path: &Path) -> bool {
+    if path.extension() == Some(OsStr::new("license")) {
+        return true;
+    }
+    if let Some(file_name) = path.file_name().and_then(|name| name.to_str()) {
+        if ["LICENSE", "LICENCE", "COPYING"]
+            .iter()
+            .any(|x| file_name.starts_with(x))
+            || file_name == "REUSE.toml"
+            || file_name.ends_with(".spdx")
+            || file_name.contains(".spdx.")
+        {
+            return true;
+        }
+    }
+    path.components()
+        .any(|c| [".git", ".reuse", "LICENSES"].iter().any(|x| c.as_os_str() == *x))
+}
+
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_os_string();
+    sidecar.push(".license");
+    PathBuf::from(sidecar)
+}
+
+pub fn check_template(template: &[String]) -> Result<(), String> {
+    for tag in [COPYRIGHT_TAG, LICENSE_TAG] {
+        if !template.iter().any(|line| line.contains(tag)) {
+            return Err(format!("template is missing {}", tag));
+        }
+    }
+    Ok(())
+}
+
+pub fn write_sidecar(path: &Path, template: &[String]) {
+    let sidecar = sidecar_path(path);
+    let content = format!("{}\n", template.join("\n").trim_end());
+    if let Ok(existing) = read(&sidecar) {
+        if existing == content.as_bytes() {
+            log::info!("License file is up-to-date {:?}", sidecar);
+            return;
+        }
+    }
+    log::info!("Writing license file {:?}", sidecar);
+    if let Err(why) = write(&sidecar, content) {
+        log::error!("Can't write license file {:?}", sidecar);
+        log::error!("{}", why);
+    }
+}
+
+// reports files without copyright and license information and licenses without a LICENSES/<id>.txt file
+pub fn lint(root: &Path, files: &[PathBuf]) -> bool {
+    let mut compliant = true;
+    let mut referenced: BTreeSet<String> = BTreeSet::new();
+    for path in files {
+        if is_ignored(path) {
+            continue;
+        }
+        let sidecar = sidecar_path(path);
+        let source = if sidecar.exists() { sidecar } else { path.clone() };
+        let text = match read(&source) {
+            Ok(buffer) => String::from_utf8_lossy(&buffer).to_string(),
+            Err(why) => {
+                log::error!("Can't read {:?}, {}", source, why);
+                compliant = false;
+                continue;
+            }
+        };
+        let mut have_copyright = false;
+        let mut have_license = false;
+        for line in text.lines() {
+            if line.contains(COPYRIGHT_TAG) {
+                have_copyright = true;
+            }
+            if let Some(captures) = SPDX_ID_PATTERN.captures(line) {
+                have_license = true;
+                if let Err(why) = validate_expression(&captures[1]) {
+                    log::error!("Invalid SPDX license expression {:?} in {:?}, {}", &captures[1], source, why);
+                    compliant = false;
+                } else {
+                    referenced.extend(license_ids(&captures[1]));
+                }
+            }
+        }
+        if !have_copyright {
+            log::error!("{:?} has no {} information", path, COPYRIGHT_TAG);
+            compliant = false;
+        }
+        if !have_license {
+            log::error!("{:?} has no {} information", path, LICENSE_TAG);
+            compliant = false;
+        }
+    }
+    let licenses = root.join("LICENSES");
+    for id in referenced {
+        let license_file = licenses.join(format!("{}.txt", id));
+        if !license_file.exists() {
+            log::error!("License {} is used but {:?} is missing", id, license_file);
+            compliant = false;
+        }
+    }
+    compliant
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn license_files_are_ignored() {
+        for name in [
+            "LICENSE",
+            "LICENSE.md",
+            "LICENCE",
+            "COPYING.LESSER",
+            "REUSE.toml",
+            "sbom.spdx",
+            "sbom.spdx.json",
+        ] {
+            assert!(is_ignored(Path::new(name)), "{}", name);
+        }
+        for name in ["src/main.rs", "license.txt", "README.md"] {
+            assert!(!is_ignored(Path::new(name)), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+// validation of SPDX license expressions, see https://spdx.github.io/spdx-spec/v2.3/SPDX-license-expressions/
+
+lazy_static! {
+    static ref LICENSE_ID: Regex =
+        Regex::new(r"^(?:(?:DocumentRef-[A-Za-z0-9.\-]+:)?LicenseRef-[A-Za-z0-9.\-]+|[A-Za-z0-9.\-]+\+?)$").unwrap();
+    static ref EXCEPTION_ID: Regex = Regex::new(r"^[A-Za-z0-9.\-]+$").unwrap();
+}
+
+const OPERATORS: [&str; 3] = ["AND", "OR", "WITH"];
+
+fn tokenize(expression: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut token = String::new();
+    for c in expression.chars() {
+        if c == '(' || c == ')' || c.is_whitespace() {
+            if !token.is_empty() {
+                tokens.push(std::mem::take(&mut token));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|t| t.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    // or-expression = and-expression *("OR" and-expression)
+    fn or_expression(&mut self) -> Result<(), String> {
+        self.and_expression()?;
+        while self.peek() == Some("OR") {
+            self.next();
+            self.and_expression()?;
+        }
+        Ok(())
+    }
+
+    // and-expression = primary *("AND" primary)
+    fn and_expression(&mut self) -> Result<(), String> {
+        self.primary()?;
+        while self.peek() == Some("AND") {
+            self.next();
+            self.primary()?;
+        }
+        Ok(())
+    }
+
+    // primary = "(" or-expression ")" / license-id ["WITH" exception-id]
+    fn primary(&mut self) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == "(" => {
+                self.or_expression()?;
+                match self.next() {
+                    Some(token) if token == ")" => Ok(()),
+                    Some(token) => Err(format!("expected ) but found {}", token)),
+                    None => Err(String::from("missing )")),
+                }
+            }
+            Some(token) if OPERATORS.contains(&token.as_str()) || token == ")" => {
+                Err(format!("expected license identifier but found {}", token))
+            }
+            Some(token) if LICENSE_ID.is_match(&token) => {
+                if self.peek() == Some("WITH") {
+                    self.next();
+                    match self.next() {
+                        Some(exception)
+                            if EXCEPTION_ID.is_match(&exception) && !OPERATORS.contains(&exception.as_str()) =>
+                        {
+                            Ok(())
+                        }
+                        Some(exception) => Err(format!("invalid exception identifier {}", exception)),
+                        None => Err(String::from("missing exception identifier after WITH")),
+                    }
+                } else {
+                    Ok(())
+                }
+            }
+            Some(token) => Err(format!("invalid license identifier {}", token)),
+            None => Err(String::from("missing license identifier")),
+        }
+    }
+}
+
+pub fn validate_expression(expression: &str) -> Result<(), String> {
+    let mut parser = Parser {
+        tokens: tokenize(expression),
+        position: 0,
+    };
+    parser.or_expression()?;
+    match parser.peek() {
+        Some(token) => Err(format!("unexpected {}", token)),
+        None => Ok(()),
+    }
+}
+
+// license and exception identifiers referenced by a well-formed expression
+pub fn license_ids(expression: &str) -> Vec<String> {
+    tokenize(expression)
+        .into_iter()
+        .filter(|token| token != "(" && token != ")" && !OPERATORS.contains(&token.as_str()))
+        .map(|token| token.trim_end_matches('+').to_string())
+        .collect()
+}
+
+// the simple expressions combined with AND and OR, "+" and "WITH <exception>" stay with their license
+pub fn license_terms(expression: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut tokens = tokenize(expression).into_iter();
+    while let Some(token) = tokens.next() {
+        match token.as_str() {
+            "(" | ")" | "AND" | "OR" => {}
+            "WITH" => {
+                if let (Some(term), Some(exception)) = (terms.last_mut(), tokens.next()) {
+                    term.push_str(" WITH ");
+                    term.push_str(&exception);
+                }
+            }
+            _ => terms.push(token),
+        }
+    }
+    terms
+}
+
+// well-known license texts, matched against a header with whitespace collapsed
+const LICENSE_TEXTS: [(&str, &[&str]); 12] = [
+    ("Apache-2.0", &["Apache License, Version 2.0"]),
+    ("MIT", &["Permission is hereby granted, free of charge"]),
+    (
+        "ISC",
+        &["Permission to use, copy, modify, and/or distribute this software for any purpose"],
+    ),
+    ("MPL-2.0", &["Mozilla Public License, v. 2.0"]),
+    (
+        "BSD-3-Clause",
+        &["Redistribution and use in source and binary forms", "Neither the name"],
+    ),
+    ("BSD-2-Clause", &["Redistribution and use in source and binary forms"]),
+    ("AGPL-3.0-or-later", &["GNU Affero General Public License", "either version 3"]),
+    ("LGPL-3.0-or-later", &["GNU Lesser General Public License", "either version 3"]),
+    (
+        "LGPL-2.1-or-later",
+        &[
+            "GNU Lesser General Public",
+            "version 2.1 of the License, or (at your option)",
+        ],
+    ),
+    ("GPL-3.0-or-later", &["GNU General Public License", "either version 3"]),
+    ("GPL-2.0-or-later", &["GNU General Public License", "either version 2"]),
+    ("GPL-2.0-only", &["GNU General Public License", "version 2"]),
+];
+
+pub fn guess_license(text: &str) -> Option<&'static str> {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    LICENSE_TEXTS
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+        .map(|(id, _)| *id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_expressions() {
+        for expression in [
+            "MIT",
+            "Apache-2.0",
+            "GPL-2.0+",
+            "LicenseRef-Acme-Proprietary",
+            "DocumentRef-spdx-tool-1.2:LicenseRef-MIT-Style-2",
+            "GPL-2.0-or-later WITH Classpath-exception-2.0",
+            "MIT OR Apache-2.0",
+            "MIT AND Apache-2.0 OR BSD-3-Clause",
+            "MIT OR Apache-2.0 AND BSD-3-Clause",
+            "(MIT OR Apache-2.0) AND BSD-3-Clause",
+            "((MIT))",
+            "GPL-2.0-only WITH Classpath-exception-2.0 AND MIT",
+            " MIT  OR\tApache-2.0 ",
+        ] {
+            assert_eq!(validate_expression(expression), Ok(()), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn with_binds_to_a_single_license() {
+        assert!(validate_expression("(MIT AND BSD-3-Clause) WITH Classpath-exception-2.0").is_err());
+        assert!(validate_expression("MIT WITH AND").is_err());
+        assert!(validate_expression("MIT WITH").is_err());
+    }
+
+    #[test]
+    fn malformed_expressions() {
+        for expression in [
+            "",
+            "   ",
+            "MIT AND",
+            "OR MIT",
+            "MIT OR OR Apache-2.0",
+            "(MIT",
+            "MIT)",
+            "(MIT OR Apache-2.0",
+            "()",
+            "MIT Apache-2.0",
+            "MIT/Apache-2.0",
+        ] {
+            assert!(validate_expression(expression).is_err(), "{:?}", expression);
+        }
+    }
+
+    #[test]
+    fn terms_keep_plus_and_exceptions() {
+        assert_eq!(
+            license_terms("(GPL-2.0+ WITH Classpath-exception-2.0 OR MIT) AND LicenseRef-Acme"),
+            vec!["GPL-2.0+ WITH Classpath-exception-2.0", "MIT", "LicenseRef-Acme"]
+        );
+        assert_eq!(
+            license_ids("GPL-2.0+ WITH Classpath-exception-2.0"),
+            vec!["GPL-2.0", "Classpath-exception-2.0"]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::spdx::validate_expression;
+use crate::SPDX_ID_PATTERN;
 use chrono::Datelike;
 use std::env;
 use std::fs::read_to_string;
@@ -5,7 +7,43 @@ use std::path::Path;
 use std::process::exit;
 use tera::{Context, Tera};
 
-pub fn read_template(path: &Path) -> Vec<String> {
+// standard license headers selectable with --license, rendered like any other template
+const BUNDLED_TEMPLATES: [(&str, &str); 11] = [
+    ("AGPL-3.0-or-later", include_str!("templates/AGPL-3.0-or-later.tera")),
+    ("Apache-2.0", include_str!("templates/Apache-2.0.tera")),
+    ("BSD-2-Clause", include_str!("templates/BSD-2-Clause.tera")),
+    ("BSD-3-Clause", include_str!("templates/BSD-3-Clause.tera")),
+    ("GPL-2.0-or-later", include_str!("templates/GPL-2.0-or-later.tera")),
+    ("GPL-3.0-or-later", include_str!("templates/GPL-3.0-or-later.tera")),
+    ("ISC", include_str!("templates/ISC.tera")),
+    ("LGPL-2.1-or-later", include_str!("templates/LGPL-2.1-or-later.tera")),
+    ("LGPL-3.0-or-later", include_str!("templates/LGPL-3.0-or-later.tera")),
+    ("MIT", include_str!("templates/MIT.tera")),
+    ("MPL-2.0", include_str!("templates/MPL-2.0.tera")),
+];
+
+pub fn bundled_licenses() -> Vec<&'static str> {
+    BUNDLED_TEMPLATES.iter().map(|(id, _)| *id).collect()
+}
+
+// REUSE mode only needs the SPDX tags, the license text is in LICENSES/<id>.txt
+const REUSE_TEMPLATE: &str = include_str!("templates/REUSE.tera");
+
+pub fn bundled_template(license: &str, variables: &[(&str, &str)], reuse: bool) -> Vec<String> {
+    let template = match BUNDLED_TEMPLATES.iter().find(|(id, _)| *id == license) {
+        Some((_, t)) if !reuse => *t,
+        Some(_) => REUSE_TEMPLATE,
+        None => {
+            log::error!("No bundled template for license {}", license);
+            exit(25);
+        }
+    };
+    log::info!("Using bundled template {}", license);
+    // rendered as is, a trailing newline gives the same layout as the same text in a --template file
+    render_template(template, variables)
+}
+
+pub fn read_template(path: &Path, variables: &[(&str, &str)]) -> Vec<String> {
     if !path.exists() {
         log::error!("Cant find template {:?}", path);
         exit(21);
@@ -18,7 +56,10 @@ pub fn read_template(path: &Path) -> Vec<String> {
             exit(22);
         }
     };
+    render_template(&template, variables)
+}
 
+fn render_template(template: &str, variables: &[(&str, &str)]) -> Vec<String> {
     let mut context = Context::new();
     let current_year = chrono::Utc::now().year().to_string();
     context.insert("year", &current_year);
@@ -26,7 +67,12 @@ pub fn read_template(path: &Path) -> Vec<String> {
         log::debug!("Adding variable to context: {} = {}", key, value);
         context.insert(key, &value);
     }
-    let result: String = match Tera::one_off(template.as_str(), &context, true) {
+    for (key, value) in variables {
+        log::debug!("Adding variable to context: {} = {}", key, value);
+        context.insert(*key, value);
+    }
+    // the result goes into source comments, not HTML, so nothing is escaped
+    let result: String = match Tera::one_off(template, &context, false) {
         Ok(r) => r,
         Err(why) => {
             log::error!("Could not create one off, {:?}", why);
@@ -36,8 +82,36 @@ pub fn read_template(path: &Path) -> Vec<String> {
 
     let mut lines: Vec<String> = Vec::new();
     for part in result.replace("\r\n", "\n").split('\n') {
+        if let Some(captures) = SPDX_ID_PATTERN.captures(part) {
+            if let Err(why) = validate_expression(&captures[1]) {
+                log::error!("Invalid SPDX license expression {:?} in template, {}", &captures[1], why);
+                exit(24);
+            }
+        }
         lines.push(part.to_string());
     }
 
     lines
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+
+    #[test]
+    fn bundled_and_file_templates_have_the_same_layout() {
+        let variables = [("holder", "Acme"), ("spdx_id", "MIT")];
+        let dir = tempfile::tempdir().unwrap();
+        for (license, text) in BUNDLED_TEMPLATES.iter().chain([("REUSE", REUSE_TEMPLATE)].iter()) {
+            let path = dir.path().join(format!("{}.tera", license));
+            write(&path, text).unwrap();
+            let bundled = if *license == "REUSE" {
+                bundled_template("MIT", &variables, true)
+            } else {
+                bundled_template(license, &variables, false)
+            };
+            assert_eq!(bundled, read_template(&path, &variables), "{}", license);
+        }
+    }
+}
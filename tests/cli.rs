@@ -0,0 +1,202 @@
+// Runs the copywrite binary on a temporary directory for the options and subcommands that are only wired up on the command line.
+
+use std::fs::{create_dir, read_to_string, write};
+use std::process::Command;
+
+#[test]
+fn bundled_license_in_reuse_mode() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path().join("main.c"), "int main() { return 0; }\n").unwrap();
+    write(dir.path().join("logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+    create_dir(dir.path().join("LICENSES")).unwrap();
+    write(dir.path().join("LICENSES").join("MIT.txt"), "MIT License\n").unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_copywrite"))
+        .arg(dir.path())
+        .args(["--license", "MIT", "--holder", "AT&T <legal@att.com>", "--reuse"])
+        .status()
+        .unwrap();
+    assert!(status.success(), "copywrite exited with {}", status);
+
+    let source = read_to_string(dir.path().join("main.c")).unwrap();
+    assert!(source.contains(" AT&T <legal@att.com>\n"), "{}", source);
+    assert!(source.contains(" * SPDX-License-Identifier: MIT\n"), "{}", source);
+    let sidecar = read_to_string(dir.path().join("logo.png.license")).unwrap();
+    assert!(sidecar.starts_with("SPDX-FileCopyrightText: "), "{}", sidecar);
+    assert!(
+        sidecar.ends_with("AT&T <legal@att.com>\nSPDX-License-Identifier: MIT\n"),
+        "{}",
+        sidecar
+    );
+}
+
+#[test]
+fn notice_skips_bsd_license_text() {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        dir.path().join("vendor.c"),
+        "/*\n * Copyright (c) 1990, 1993 The Regents of the University of California.\n \
+         * All rights reserved.\n *\n \
+         * Redistribution and use in source and binary forms, with or without\n \
+         * modification, are permitted provided that the following conditions\n \
+         * are met:\n \
+         * 1. Redistributions of source code must retain the above copyright\n \
+         *    notice, this list of conditions and the following disclaimer.\n \
+         * 2. Redistributions in binary form must reproduce the above copyright\n \
+         *    notice, this list of conditions and the following disclaimer in the\n \
+         *    documentation and/or other materials provided with the distribution.\n \
+         */\n\nint vendor() { return 0; }\n",
+    )
+    .unwrap();
+    write(
+        dir.path().join("main.c"),
+        "/*\n * Copyright 2024 Acme Inc.\n */\n\nint main() { return 0; }\n",
+    )
+    .unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_copywrite"))
+        .arg("notice")
+        .arg(dir.path())
+        .args(["--owner", "Acme"])
+        .status()
+        .unwrap();
+    assert!(status.success(), "copywrite exited with {}", status);
+
+    let notice = read_to_string(dir.path().join("NOTICE")).unwrap();
+    assert_eq!(
+        notice,
+        "NOTICE\n\nThis product includes software with the following copyright notices:\n\n\
+         Copyright (c) 1990, 1993 The Regents of the University of California.\n"
+    );
+}
+
+#[test]
+fn git_directory_is_left_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let hooks = dir.path().join(".git").join("hooks");
+    std::fs::create_dir_all(&hooks).unwrap();
+    let hook = "#!/bin/sh\nexec true\n";
+    write(hooks.join("pre-commit.sample"), hook).unwrap();
+    write(hooks.join("post-update"), hook).unwrap();
+    write(dir.path().join("build"), hook).unwrap();
+    let templates = tempfile::tempdir().unwrap();
+    let template = templates.path().join("header.tera");
+    write(&template, "Copyright (c) 2024 Acme Corp.\n").unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_copywrite"))
+        .arg(dir.path())
+        .arg("-t")
+        .arg(&template)
+        .status()
+        .unwrap();
+    assert!(status.success(), "copywrite exited with {}", status);
+
+    assert_eq!(read_to_string(hooks.join("pre-commit.sample")).unwrap(), hook);
+    assert_eq!(read_to_string(hooks.join("post-update")).unwrap(), hook);
+    let script = read_to_string(dir.path().join("build")).unwrap();
+    assert!(script.contains("# Copyright (c) 2024 Acme Corp.\n"), "{}", script);
+}
+
+#[test]
+fn reuse_in_git_index_mode_stages_license_files() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path().join("main.c"), "int main() { return 0; }\n").unwrap();
+    write(dir.path().join("logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+    write(dir.path().join("tool.py"), "print('not checked with --language c')\n").unwrap();
+    create_dir(dir.path().join("LICENSES")).unwrap();
+    write(dir.path().join("LICENSES").join("MIT.txt"), "MIT License\n").unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+    index.write().unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_copywrite"))
+        .current_dir(dir.path())
+        .arg(".")
+        .args([
+            "--gitindex",
+            "--language",
+            "c",
+            "--license",
+            "MIT",
+            "--holder",
+            "Acme",
+            "--reuse",
+        ])
+        .status()
+        .unwrap();
+    assert!(status.success(), "copywrite exited with {}", status);
+
+    let mut index = repo.index().unwrap();
+    index.read(true).unwrap();
+    assert!(index.get_path(std::path::Path::new("logo.png.license"), 0).is_some());
+    assert_eq!(
+        read_to_string(dir.path().join("tool.py")).unwrap(),
+        "print('not checked with --language c')\n"
+    );
+}
+
+#[test]
+fn notice_lists_third_party_header_below_ours() {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        dir.path().join("vendor.c"),
+        "/*\n * Copyright (c) 2015 Google LLC\n * SPDX-License-Identifier: Apache-2.0\n */\n\nint vendor() { return 0; }\n",
+    )
+    .unwrap();
+    let templates = tempfile::tempdir().unwrap();
+    let template = templates.path().join("header.tera");
+    write(&template, "Copyright (c) 2024 Acme Corp.\nSPDX-License-Identifier: MIT\n").unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_copywrite"))
+        .arg(dir.path())
+        .arg("-t")
+        .arg(&template)
+        .args(["--owner", "Acme", "--third-party", "above"])
+        .status()
+        .unwrap();
+    assert!(status.success(), "copywrite exited with {}", status);
+    let source = read_to_string(dir.path().join("vendor.c")).unwrap();
+    assert!(source.starts_with("/*\n * Copyright (c) 2024 Acme Corp.\n"), "{}", source);
+
+    let status = Command::new(env!("CARGO_BIN_EXE_copywrite"))
+        .arg("notice")
+        .arg(dir.path())
+        .args(["--owner", "Acme"])
+        .status()
+        .unwrap();
+    assert!(status.success(), "copywrite exited with {}", status);
+    let notice = read_to_string(dir.path().join("NOTICE")).unwrap();
+    assert_eq!(
+        notice,
+        "NOTICE\n\nThis product includes software with the following copyright notices:\n\n\
+         Copyright (c) 2015 Google LLC\n"
+    );
+}
+
+#[test]
+fn notice_check_fails_when_out_of_date() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path().join("vendor.c"), "// Copyright (c) 2015 Google LLC\n\nint vendor;\n").unwrap();
+    write(dir.path().join("NOTICE"), "NOTICE\n").unwrap();
+    let check = || {
+        Command::new(env!("CARGO_BIN_EXE_copywrite"))
+            .arg("notice")
+            .arg(dir.path())
+            .args(["--owner", "Acme", "--check"])
+            .status()
+            .unwrap()
+    };
+
+    assert_eq!(check().code(), Some(15));
+    assert_eq!(read_to_string(dir.path().join("NOTICE")).unwrap(), "NOTICE\n");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_copywrite"))
+        .arg("notice")
+        .arg(dir.path())
+        .args(["--owner", "Acme"])
+        .status()
+        .unwrap();
+    assert!(status.success(), "copywrite exited with {}", status);
+    assert!(check().success());
+}
@@ -0,0 +1,166 @@
+// Golden-file tests, every case in tests/fixtures/<language>/<case>/ has an input/ and an expected/ directory holding
+// the same file name. The input is copied to a temporary directory, updated with tests/fixtures/template.tera and
+// compared byte for byte with the expected file, then updated again to check that a second run changes nothing.
+// The input also has to pass `copywrite verify`.
+// A case can hold an options file with owner=<regex> and third-party=skip|above|append lines.
+// Run with COPYWRITE_BLESS=1 to write the actual output to expected/ when adding cases.
+
+use copywrite::copywriter::{copywrite_path, verify_path, Options, ThirdParty};
+use copywrite::template::read_template;
+use regex::Regex;
+use std::env;
+use std::ffi::OsString;
+use std::fs::{copy, create_dir_all, read, read_dir, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = read_dir(dir)
+        .unwrap_or_else(|why| panic!("Can't read {:?}, {}", dir, why))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    entries
+}
+
+fn options(case: &Path) -> Options {
+    let mut options = Options::default();
+    for line in read_to_string(case.join("options")).unwrap_or_default().lines() {
+        match line.split_once('=') {
+            Some(("owner", owner)) => options.owner = Some(Regex::new(owner).unwrap()),
+            Some(("third-party", "skip")) => options.third_party = ThirdParty::Skip,
+            Some(("third-party", "above")) => options.third_party = ThirdParty::Above,
+            Some(("third-party", "append")) => options.third_party = ThirdParty::Append,
+            _ => panic!("Unknown option {:?} in {:?}", line, case),
+        }
+    }
+    options
+}
+
+fn run(path: &Path, template: &[String], options: &Options) -> Vec<u8> {
+    copywrite_path(&[OsString::from(path)], template, &None, options);
+    read(path).unwrap()
+}
+
+fn check_case(case: &Path, template: &[String]) -> Vec<String> {
+    let mut failures = Vec::new();
+    let name = case.strip_prefix(fixtures()).unwrap().display().to_string();
+    let inputs = entries(&case.join("input"));
+    assert_eq!(inputs.len(), 1, "{} needs exactly one input file", name);
+    let file_name = inputs[0].file_name().unwrap();
+    let expected_path = case.join("expected").join(file_name);
+    let options = options(case);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(file_name);
+    copy(&inputs[0], &path).unwrap();
+    for failure in verify_path(&[OsString::from(&path)], template, &None, &options) {
+        failures.push(format!("{}: verify failed, {}", name, failure.reason));
+    }
+    let actual = run(&path, template, &options);
+    if env::var_os("COPYWRITE_BLESS").is_some() {
+        create_dir_all(expected_path.parent().unwrap()).unwrap();
+        write(&expected_path, &actual).unwrap();
+    }
+    let expected = read(&expected_path).unwrap_or_else(|why| panic!("Can't read {:?}, {}", expected_path, why));
+    if actual != expected {
+        failures.push(format!(
+            "{}: output differs\n--- expected\n{}--- actual\n{}",
+            name,
+            String::from_utf8_lossy(&expected),
+            String::from_utf8_lossy(&actual)
+        ));
+    } else if run(&path, template, &options) != expected {
+        failures.push(format!("{}: second run changed the file", name));
+    }
+    failures
+}
+
+fn check_language(language: &str) {
+    let template = read_template(&fixtures().join("template.tera"), &[]);
+    let failures: Vec<String> = entries(&fixtures().join(language))
+        .iter()
+        .flat_map(|case| check_case(case, &template))
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+macro_rules! languages {
+    ( $( $name:ident => $language:expr ),* $(,)? ) => {
+        $(
+            #[test]
+            fn $name() {
+                check_language($language);
+            }
+        )*
+    };
+}
+
+languages! {
+    asm => "asm",
+    astro => "astro",
+    batch => "batch",
+    blade => "blade",
+    c => "c",
+    clojure => "clojure",
+    cmake => "cmake",
+    cpp => "cpp",
+    csharp => "csharp",
+    css => "css",
+    dockerfile => "dockerfile",
+    elisp => "elisp",
+    elixir => "elixir",
+    erb => "erb",
+    erlang => "erlang",
+    fsharp => "fsharp",
+    go => "go",
+    groovy => "groovy",
+    haskell => "haskell",
+    html => "html",
+    ini => "ini",
+    java => "java",
+    javascript => "javascript",
+    jsp => "jsp",
+    kotlin => "kotlin",
+    latex => "latex",
+    lua => "lua",
+    makefile => "makefile",
+    markdown => "markdown",
+    mathematica => "mathematica",
+    matlab => "matlab",
+    nix => "nix",
+    objective_c => "objective-c",
+    ocaml => "ocaml",
+    pascal => "pascal",
+    perl => "perl",
+    php => "php",
+    powershell => "powershell",
+    prolog => "prolog",
+    proto => "proto",
+    python => "python",
+    r => "r",
+    racket => "racket",
+    razor => "razor",
+    resx => "resx",
+    rst => "rst",
+    ruby => "ruby",
+    rust => "rust",
+    scheme => "scheme",
+    script => "script",
+    sml => "sml",
+    sql => "sql",
+    svelte => "svelte",
+    svg => "svg",
+    swift => "swift",
+    terraform => "terraform",
+    toml => "toml",
+    typescript => "typescript",
+    verilog => "verilog",
+    vhdl => "vhdl",
+    vue => "vue",
+    xml => "xml",
+    yaml => "yaml",
+}
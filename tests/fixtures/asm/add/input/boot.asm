@@ -0,0 +1,2 @@
+section .text
+global _start
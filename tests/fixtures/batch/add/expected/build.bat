@@ -0,0 +1,8 @@
+@echo off
+::
+:: Copyright (c) 2024 Acme Corp.
+:: Licensed under the Apache License, Version 2.0.
+::
+::
+setlocal
+echo hello
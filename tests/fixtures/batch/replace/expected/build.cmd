@@ -0,0 +1,8 @@
+@ECHO OFF
+::
+:: Copyright (c) 2024 Acme Corp.
+:: Licensed under the Apache License, Version 2.0.
+::
+::
+
+echo hello
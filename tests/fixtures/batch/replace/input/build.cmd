@@ -0,0 +1,5 @@
+@ECHO OFF
+REM Copyright (c) 2019 Old Corp.
+REM All rights reserved.
+
+echo hello
@@ -0,0 +1 @@
+<div>{{ $name }}</div>
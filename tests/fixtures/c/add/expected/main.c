@@ -0,0 +1,10 @@
+/*
+ * Copyright (c) 2024 Acme Corp.
+ * Licensed under the Apache License, Version 2.0.
+ *
+ */
+#include <stdio.h>
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,6 @@
+/*
+ * Copyright 2015 Google LLC. Licensed under the Apache License, Version 2.0.
+ * Copyright (c) 2024 Acme Corp.
+ */
+
+int main() { return 0; }
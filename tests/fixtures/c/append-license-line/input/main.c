@@ -0,0 +1,5 @@
+/*
+ * Copyright 2015 Google LLC. Licensed under the Apache License, Version 2.0.
+ */
+
+int main() { return 0; }
@@ -0,0 +1,4 @@
+// Copyright (c) 2019 Old Corp.
+// All rights reserved.
+
+int util(void);
@@ -0,0 +1,10 @@
+/*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ */
+
+#include <stdio.h>
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,6 @@
+##
+## Copyright (c) 2024 Acme Corp.
+## Licensed under the Apache License, Version 2.0.
+##
+##
+find_path(FOO_INCLUDE_DIR foo.h)
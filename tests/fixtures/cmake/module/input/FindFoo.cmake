@@ -0,0 +1 @@
+find_path(FOO_INCLUDE_DIR foo.h)
@@ -0,0 +1,10 @@
+/*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ */
+
+using System;
+
+class Program {
+    static void Main() {}
+}
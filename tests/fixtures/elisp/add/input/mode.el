@@ -0,0 +1 @@
+(provide 'mode)
@@ -0,0 +1,4 @@
+;;; mode.el --- A mode -*- lexical-binding: t -*-
+;; Copyright (C) 2019 Old Corp.
+
+;;; Code:
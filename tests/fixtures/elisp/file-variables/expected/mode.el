@@ -0,0 +1,11 @@
+;;; mode.el --- A mode -*- lexical-binding: t -*-
+;;;
+;;; Copyright (c) 2024 Acme Corp.
+;;; Licensed under the Apache License, Version 2.0.
+;;;
+;;;
+
+
+;;; Code:
+
+(provide 'mode)
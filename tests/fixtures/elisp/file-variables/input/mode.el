@@ -0,0 +1,5 @@
+;;; mode.el --- A mode -*- lexical-binding: t -*-
+
+;;; Code:
+
+(provide 'mode)
@@ -0,0 +1,10 @@
+;;; mode.el --- A mode
+;;;
+;;; Copyright (c) 2024 Acme Corp.
+;;; Licensed under the Apache License, Version 2.0.
+;;;
+;;;
+;;; Commentary:
+;; Does things.
+;;; Code:
+(provide 'mode)
@@ -0,0 +1,6 @@
+;;; mode.el --- A mode
+;; Copyright (C) 2019 Old Corp.
+;;; Commentary:
+;; Does things.
+;;; Code:
+(provide 'mode)
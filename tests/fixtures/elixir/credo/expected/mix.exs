@@ -0,0 +1,8 @@
+# credo:disable-for-this-file
+##
+## Copyright (c) 2024 Acme Corp.
+## Licensed under the Apache License, Version 2.0.
+##
+##
+defmodule Mix do
+end
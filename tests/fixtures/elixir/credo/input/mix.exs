@@ -0,0 +1,3 @@
+# credo:disable-for-this-file
+defmodule Mix do
+end
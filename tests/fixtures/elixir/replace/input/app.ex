@@ -0,0 +1,6 @@
+##
+## Copyright (c) 2019 Old Corp.
+##
+
+defmodule App do
+end
@@ -0,0 +1,2 @@
+-module(app).
+-export([start/0]).
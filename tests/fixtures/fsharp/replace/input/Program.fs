@@ -0,0 +1,9 @@
+(*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ *)
+
+module Program
+
+[<EntryPoint>]
+let main _ = 0
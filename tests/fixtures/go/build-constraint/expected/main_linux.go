@@ -0,0 +1,11 @@
+//go:build linux && amd64
+// +build linux,amd64
+
+/*
+ * Copyright (c) 2024 Acme Corp.
+ * Licensed under the Apache License, Version 2.0.
+ *
+ */
+package main
+
+func main() {}
@@ -0,0 +1,6 @@
+//go:build linux
+package main
+
+import "fmt"
+
+func main() { fmt.Println() }
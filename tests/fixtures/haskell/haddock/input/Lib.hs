@@ -0,0 +1,2 @@
+-- | The library module.
+module Lib where
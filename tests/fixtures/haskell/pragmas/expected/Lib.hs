@@ -0,0 +1,8 @@
+{-# LANGUAGE OverloadedStrings #-}
+{-# OPTIONS_GHC -Wall #-}
+--
+-- Copyright (c) 2024 Acme Corp.
+-- Licensed under the Apache License, Version 2.0.
+--
+--
+module Lib where
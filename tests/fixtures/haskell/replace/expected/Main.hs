@@ -0,0 +1,10 @@
+--
+-- Copyright (c) 2024 Acme Corp.
+-- Licensed under the Apache License, Version 2.0.
+--
+--
+
+module Main where
+
+main :: IO ()
+main = pure ()
@@ -0,0 +1,9 @@
+--
+-- Copyright (c) 2019 Old Corp.
+-- All rights reserved.
+--
+
+module Main where
+
+main :: IO ()
+main = pure ()
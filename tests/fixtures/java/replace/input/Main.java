@@ -0,0 +1,8 @@
+/*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ */
+
+public class Main {
+    public static void main(String[] args) {}
+}
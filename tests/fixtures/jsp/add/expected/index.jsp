@@ -0,0 +1,8 @@
+<%@ page contentType="text/html;charset=UTF-8" %>
+<%@ taglib prefix="c" uri="http://java.sun.com/jsp/jstl/core" %>
+<%--
+    Copyright (c) 2024 Acme Corp.
+    Licensed under the Apache License, Version 2.0.
+
+--%>
+<html></html>
@@ -0,0 +1,7 @@
+--
+-- Copyright (c) 2024 Acme Corp.
+-- Licensed under the Apache License, Version 2.0.
+--
+--
+local M = {}
+return M
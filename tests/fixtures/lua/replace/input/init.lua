@@ -0,0 +1,7 @@
+--
+-- Copyright (c) 2019 Old Corp.
+-- All rights reserved.
+--
+
+local M = {}
+return M
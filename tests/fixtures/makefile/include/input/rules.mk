@@ -0,0 +1 @@
+CFLAGS += -O2
@@ -0,0 +1,2 @@
+BeginPackage["Package`"]
+EndPackage[]
@@ -0,0 +1,3 @@
+function y = compute(x)
+  y = x * 2;
+end
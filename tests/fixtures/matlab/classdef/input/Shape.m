@@ -0,0 +1,2 @@
+classdef Shape
+end
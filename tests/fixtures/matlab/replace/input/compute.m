@@ -0,0 +1,6 @@
+% Copyright (c) 2019 Old Corp.
+% All rights reserved.
+
+function y = compute(x)
+  y = x * 2;
+end
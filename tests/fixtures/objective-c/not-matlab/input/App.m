@@ -0,0 +1,4 @@
+#import "App.h"
+
+@implementation App
+@end
@@ -0,0 +1,10 @@
+/*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ */
+
+#import <Foundation/Foundation.h>
+
+int main() {
+    return 0;
+}
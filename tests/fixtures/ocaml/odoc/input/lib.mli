@@ -0,0 +1,2 @@
+(** The library. *)
+val f : int -> int
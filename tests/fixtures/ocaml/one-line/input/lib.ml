@@ -0,0 +1,2 @@
+(* Copyright (c) 2019 Old Corp. *)
+let f x = x
@@ -0,0 +1,12 @@
+(*
+ * Copyright (c) 2024 Acme Corp.
+ * Licensed under the Apache License, Version 2.0.
+ *
+ *)
+unit Strings;
+
+interface
+
+implementation
+
+end.
@@ -0,0 +1,7 @@
+unit Strings;
+
+interface
+
+implementation
+
+end.
@@ -0,0 +1,10 @@
+(*
+ * Copyright (c) 2024 Acme Corp.
+ * Licensed under the Apache License, Version 2.0.
+ *
+ *)
+
+program Hello;
+begin
+  WriteLn('hello');
+end.
@@ -0,0 +1,9 @@
+(*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ *)
+
+program Hello;
+begin
+  WriteLn('hello');
+end.
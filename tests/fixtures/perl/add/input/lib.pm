@@ -0,0 +1,3 @@
+package Lib;
+
+1;
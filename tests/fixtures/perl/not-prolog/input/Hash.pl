@@ -0,0 +1,2 @@
+use strict;
+%h = (a => 1);
@@ -0,0 +1,9 @@
+##
+## Copyright (c) 2024 Acme Corp.
+## Licensed under the Apache License, Version 2.0.
+##
+##
+
+package Lib;
+
+1;
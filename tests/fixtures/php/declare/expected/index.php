@@ -0,0 +1,12 @@
+<?php
+
+declare(strict_types=1);
+/*
+ * Copyright (c) 2024 Acme Corp.
+ * Licensed under the Apache License, Version 2.0.
+ *
+ */
+
+namespace App;
+
+echo "hello";
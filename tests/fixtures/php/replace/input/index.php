@@ -0,0 +1,7 @@
+<?php
+/*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ */
+
+echo 'hello';
@@ -0,0 +1,11 @@
+##
+## Copyright (c) 2024 Acme Corp.
+## Licensed under the Apache License, Version 2.0.
+##
+##
+
+<#
+.SYNOPSIS
+  Gets a thing.
+#>
+param()
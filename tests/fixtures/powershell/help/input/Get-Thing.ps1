@@ -0,0 +1,5 @@
+<#
+.SYNOPSIS
+  Gets a thing.
+#>
+param()
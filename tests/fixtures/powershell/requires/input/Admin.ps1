@@ -0,0 +1,3 @@
+#Requires -Version 7
+#Requires -RunAsAdministrator
+Write-Host 'hello'
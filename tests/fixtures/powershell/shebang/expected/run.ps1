@@ -0,0 +1,7 @@
+#!/usr/bin/env pwsh
+##
+## Copyright (c) 2024 Acme Corp.
+## Licensed under the Apache License, Version 2.0.
+##
+##
+Write-Host 'hello'
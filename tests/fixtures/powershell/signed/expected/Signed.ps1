@@ -0,0 +1,5 @@
+Write-Host 'hello'
+
+# SIG # Begin signature block
+# MIIFuQYJKoZIhvcNAQcCoIIFqjCCBaYCAQExCzAJBgUrDgMCGgUAMGkGCisGAQQB
+# SIG # End signature block
@@ -0,0 +1,3 @@
+:- module(family, [parent/2]).
+
+parent(tom, bob).
@@ -0,0 +1,8 @@
+#
+# Copyright (c) 2024 Acme Corp.
+# Licensed under the Apache License, Version 2.0.
+#
+#
+import sys
+
+print(sys.argv)
@@ -0,0 +1,5 @@
+#
+# Copyright (c) 2024 Acme Corp.
+# Licensed under the Apache License, Version 2.0.
+#
+#
@@ -0,0 +1,7 @@
+##
+## Copyright (c) 2019 Old Corp.
+## All rights reserved.
+##
+
+class App
+end
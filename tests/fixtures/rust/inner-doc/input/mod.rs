@@ -0,0 +1,3 @@
+//! Module documentation.
+
+pub fn f() {}
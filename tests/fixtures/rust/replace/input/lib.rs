@@ -0,0 +1,8 @@
+/*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ */
+
+pub fn answer() -> u32 {
+    42
+}
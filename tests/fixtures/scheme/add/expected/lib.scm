@@ -0,0 +1,6 @@
+;;
+;; Copyright (c) 2024 Acme Corp.
+;; Licensed under the Apache License, Version 2.0.
+;;
+;;
+(define (f x) x)
@@ -0,0 +1 @@
+(define (f x) x)
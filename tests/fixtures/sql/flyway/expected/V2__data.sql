@@ -0,0 +1,7 @@
+-- flyway:executeInTransaction=false
+--
+-- Copyright (c) 2024 Acme Corp.
+-- Licensed under the Apache License, Version 2.0.
+--
+--
+CREATE INDEX CONCURRENTLY i ON t (id);
@@ -0,0 +1,4 @@
+--liquibase formatted sql
+--comment: creates the table
+--changeset alice:1
+CREATE TABLE t (id INT);
@@ -0,0 +1,5 @@
+--liquibase formatted sql
+-- Copyright (c) 2019 Old Corp.
+--changeset alice:1
+CREATE TABLE t (id INT);
+--rollback DROP TABLE t;
@@ -0,0 +1,10 @@
+--liquibase formatted sql
+--
+-- Copyright (c) 2024 Acme Corp.
+-- Licensed under the Apache License, Version 2.0.
+--
+--
+
+
+--changeset alice:1
+CREATE TABLE t (id INT);
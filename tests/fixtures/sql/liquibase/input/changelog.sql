@@ -0,0 +1,4 @@
+--liquibase formatted sql
+
+--changeset alice:1
+CREATE TABLE t (id INT);
@@ -0,0 +1,3 @@
+-- comment tables of the reporting schema
+-- Copyright (c) 2019 Old Corp.
+CREATE TABLE t (id INT);
@@ -0,0 +1,7 @@
+#!/usr/bin/swift
+/*
+ * Copyright (c) 2024 Acme Corp.
+ * Licensed under the Apache License, Version 2.0.
+ *
+ */
+print("hello")
@@ -0,0 +1,4 @@
+/**
+ * @file Utilities.
+ */
+export const x = 1;
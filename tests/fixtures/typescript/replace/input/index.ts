@@ -0,0 +1,6 @@
+/*
+ * Copyright (c) 2019 Old Corp.
+ * All rights reserved.
+ */
+
+export const answer = 42;
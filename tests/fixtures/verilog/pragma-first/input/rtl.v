@@ -0,0 +1,3 @@
+/* verilator lint_off UNUSED */
+module rtl;
+endmodule
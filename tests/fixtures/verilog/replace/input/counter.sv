@@ -0,0 +1,6 @@
+/*
+ * Copyright (c) 2019 Old Corp.
+ */
+
+module counter(input logic clk);
+endmodule
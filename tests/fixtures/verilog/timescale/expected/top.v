@@ -0,0 +1,9 @@
+`timescale 1ns / 1ps
+`default_nettype none
+/*
+ * Copyright (c) 2024 Acme Corp.
+ * Licensed under the Apache License, Version 2.0.
+ *
+ */
+module top;
+endmodule
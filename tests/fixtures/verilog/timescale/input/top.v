@@ -0,0 +1,4 @@
+`timescale 1ns / 1ps
+`default_nettype none
+module top;
+endmodule
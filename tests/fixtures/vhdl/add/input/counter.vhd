@@ -0,0 +1,2 @@
+library ieee;
+use ieee.std_logic_1164.all;
@@ -0,0 +1,4 @@
+-- Copyright (c) 2019 Old Corp.
+-- synthesis translate_off
+use std.textio.all;
+-- synthesis translate_on
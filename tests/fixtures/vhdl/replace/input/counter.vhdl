@@ -0,0 +1,5 @@
+--
+-- Copyright (c) 2019 Old Corp.
+--
+
+library ieee;